@@ -0,0 +1,509 @@
+// Headless balance simulator: plays missions with a bot pilot and prints stats.
+//
+//   cargo run --release --bin balance_sim -- --level 3 --difficulty blackhole --runs 200
+//
+// Nothing here opens a window, so it can run on CI or a server.
+
+use macroquad::prelude::*;
+use macroquad::rand::RandGenerator;
+use rust_in_space::components::{
+    Asteroid, Bullet, BulletStyle, Difficulty, EnemyType, LootType, PlayerInput,
+};
+use rust_in_space::data::{GameData, DATA_DIR};
+use rust_in_space::events::GameEvent;
+use rust_in_space::game::{spawn_enemy, update_game, Game, TICK_DT};
+use rust_in_space::localization::{Localization, LANG_DIR};
+use rust_in_space::missions::mission_text;
+use rust_in_space::systems::{angle_diff, generate_loot, LootSource};
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::Instant;
+
+const STRESS_TICKS: u32 = 600;
+
+struct Options {
+    runs: u32,
+    level: u32,
+    difficulty: Difficulty,
+    seed: u64,
+    pilot: Pilot,
+    max_time: f32,
+    arena: Vec2,
+    loot_rolls: u32,
+    stress: Option<usize>,
+    data_dir: PathBuf,
+}
+
+#[derive(Clone, Copy)]
+enum Pilot {
+    Bot,    // Hunts enemies and asteroids, then sweeps up loot
+    Turret, // Scripted: spins in place and keeps firing
+}
+
+// Outcome of a single simulated mission
+struct RunStats {
+    completed: bool,
+    died: bool,
+    time: f32,
+    combat: CombatStats,
+}
+
+// Counted from the game's events by a subscriber
+#[derive(Default)]
+struct CombatStats {
+    damage_taken: f32,
+    kills: u32,
+    loot_dropped: Vec<LootType>,
+}
+
+impl CombatStats {
+    fn record(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::PlayerDamaged { amount, .. } => self.damage_taken += amount,
+            GameEvent::EnemyDestroyed { .. } => self.kills += 1,
+            GameEvent::LootSpawned { item, .. } => self.loot_dropped.push(item.clone()),
+            _ => {}
+        }
+    }
+}
+
+fn usage() -> ! {
+    eprintln!(
+        "Usage: balance_sim [--runs N] [--level N] [--difficulty nebula|supernova|blackhole]\n\
+         \x20                  [--seed N] [--pilot bot|turret] [--max-time SECONDS]\n\
+         \x20                  [--arena WIDTHxHEIGHT] [--loot-rolls N] [--stress ENTITIES]\n\
+         \x20                  [--data DIR]"
+    );
+    std::process::exit(2);
+}
+
+fn parse_options() -> Options {
+    let mut opts = Options {
+        runs: 100,
+        level: 1,
+        difficulty: Difficulty::Supernova,
+        seed: 1,
+        pilot: Pilot::Bot,
+        max_time: 300.0,
+        arena: vec2(1280.0, 720.0),
+        loot_rolls: 100_000,
+        stress: None,
+        data_dir: PathBuf::from(DATA_DIR),
+    };
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut i = 0;
+    while i < args.len() {
+        let value = args
+            .get(i + 1)
+            .map(String::as_str)
+            .unwrap_or_else(|| usage());
+        match args[i].as_str() {
+            "--runs" => opts.runs = value.parse().unwrap_or_else(|_| usage()),
+            "--level" => opts.level = value.parse().unwrap_or_else(|_| usage()),
+            "--seed" => opts.seed = value.parse().unwrap_or_else(|_| usage()),
+            "--max-time" => opts.max_time = value.parse().unwrap_or_else(|_| usage()),
+            "--loot-rolls" => opts.loot_rolls = value.parse().unwrap_or_else(|_| usage()),
+            "--stress" => opts.stress = Some(value.parse().unwrap_or_else(|_| usage())),
+            "--data" => opts.data_dir = PathBuf::from(value),
+            "--difficulty" => {
+                opts.difficulty = match value.to_lowercase().as_str() {
+                    "nebula" | "easy" => Difficulty::Nebula,
+                    "supernova" | "normal" => Difficulty::Supernova,
+                    "blackhole" | "hard" => Difficulty::BlackHole,
+                    _ => usage(),
+                }
+            }
+            "--pilot" => {
+                opts.pilot = match value {
+                    "bot" => Pilot::Bot,
+                    "turret" => Pilot::Turret,
+                    _ => usage(),
+                }
+            }
+            "--arena" => {
+                let (w, h) = value.split_once('x').unwrap_or_else(|| usage());
+                opts.arena = vec2(
+                    w.parse().unwrap_or_else(|_| usage()),
+                    h.parse().unwrap_or_else(|_| usage()),
+                );
+            }
+            _ => usage(),
+        }
+        i += 2;
+    }
+    if opts.level == 0 {
+        usage();
+    }
+    opts
+}
+
+fn nearest(from: Vec2, points: impl Iterator<Item = Vec2>) -> Option<Vec2> {
+    points.min_by(|a, b| {
+        from.distance_squared(*a)
+            .total_cmp(&from.distance_squared(*b))
+    })
+}
+
+fn bot_input(game: &Game) -> PlayerInput {
+    let ship = &game.ship;
+
+    // Kamikazes close by come first, then anything that shoots back, then rocks
+    let kamikaze = nearest(
+        ship.pos,
+        game.enemy_ships
+            .iter()
+            .filter(|e| e.enemy_type == EnemyType::Kamikaze)
+            .map(|e| e.pos),
+    )
+    .filter(|p| p.distance(ship.pos) < 300.0);
+    let target = kamikaze
+        .or_else(|| nearest(ship.pos, game.enemy_ships.iter().map(|e| e.pos)))
+        .or_else(|| nearest(ship.pos, game.asteroids.iter().map(|a| a.pos)));
+    let loot = nearest(ship.pos, game.loot_items.iter().map(|l| l.pos));
+
+    // Go fetch loot when nothing is threatening, otherwise fight
+    let (aim, collecting) = match (target, loot) {
+        (Some(t), Some(l)) if kamikaze.is_none() && l.distance(ship.pos) < t.distance(ship.pos) => {
+            (l, true)
+        }
+        (Some(t), _) => (t, false),
+        (None, Some(l)) => (l, true),
+        (None, None) => (ship.pos + vec2(1.0, 0.0), false),
+    };
+
+    let to_aim = aim - ship.pos;
+    let diff = angle_diff(ship.rotation, to_aim.y.atan2(to_aim.x).to_degrees());
+    let aligned = diff.abs() < 10.0;
+    let slow = ship.vel.length() < 120.0;
+
+    PlayerInput {
+        turn: if diff < -3.0 {
+            -1.0
+        } else if diff > 3.0 {
+            1.0
+        } else {
+            0.0
+        },
+        thrust: if aligned && slow && (collecting || to_aim.length() > 350.0) {
+            1.0
+        } else {
+            0.0
+        },
+        fire: aligned && !collecting,
+        ..Default::default()
+    }
+}
+
+fn turret_input(_game: &Game) -> PlayerInput {
+    PlayerInput {
+        turn: 1.0,
+        fire: true,
+        ..Default::default()
+    }
+}
+
+fn run_mission(opts: &Options, data: &GameData, seed: u64) -> RunStats {
+    let mut game = Game::new(opts.arena, seed, data.clone());
+    game.difficulty = opts.difficulty;
+    game.reset(seed);
+    game.current_level_idx = opts.level;
+    game.current_mission = game.data.missions.get(opts.level);
+    game.start_mission();
+    let combat = Rc::new(RefCell::new(CombatStats::default()));
+    let sink = Rc::clone(&combat);
+    game.subscribe(move |_: &mut Game, event: &GameEvent| sink.borrow_mut().record(event));
+
+    let max_ticks = (opts.max_time / TICK_DT) as u32;
+    let mut stats = RunStats {
+        completed: false,
+        died: false,
+        time: 0.0,
+        combat: CombatStats::default(),
+    };
+
+    for tick in 0..max_ticks {
+        if game.is_mission_complete() {
+            stats.completed = true;
+            break;
+        }
+
+        let input = match opts.pilot {
+            Pilot::Bot => bot_input(&game),
+            Pilot::Turret => turret_input(&game),
+        };
+        let game_over = update_game(&mut game, &input, TICK_DT);
+
+        stats.time = (tick + 1) as f32 * TICK_DT;
+        if game_over {
+            stats.died = true;
+            break;
+        }
+    }
+    stats.combat = combat.take();
+    stats
+}
+
+fn difficulty_name(difficulty: Difficulty) -> &'static str {
+    match difficulty {
+        Difficulty::Nebula => "Nebula",
+        Difficulty::Supernova => "Supernova",
+        Difficulty::BlackHole => "Black Hole",
+    }
+}
+
+fn loot_name(item: &LootType) -> &'static str {
+    match item {
+        LootType::Scrap(_) => "Scrap",
+        LootType::RareMetal(_) => "Rare metal",
+        LootType::HealthPack(_) => "Health pack",
+        LootType::RapidFireBoost => "Rapid fire",
+        LootType::BigBulletBoost => "Big bullet",
+        LootType::Shield(_) => "Shield",
+    }
+}
+
+fn loot_amount(item: &LootType) -> Option<u32> {
+    match item {
+        LootType::Scrap(n) | LootType::RareMetal(n) | LootType::Shield(n) => Some(*n),
+        LootType::HealthPack(hp) => Some(*hp as u32),
+        LootType::RapidFireBoost | LootType::BigBulletBoost => None,
+    }
+}
+
+fn report_missions(opts: &Options, data: &GameData, lang: &Localization, runs: &[RunStats]) {
+    let mission = data.missions.get(opts.level);
+    println!(
+        "Mission {} \"{}\" on {}, {} runs, pilot: {}",
+        mission.level_id,
+        mission_text(&mission, &mission.title_key, lang),
+        difficulty_name(opts.difficulty),
+        runs.len(),
+        match opts.pilot {
+            Pilot::Bot => "bot",
+            Pilot::Turret => "turret",
+        }
+    );
+
+    let count = runs.len().max(1) as f32;
+    let completed: Vec<&RunStats> = runs.iter().filter(|r| r.completed).collect();
+    let deaths = runs.iter().filter(|r| r.died).count();
+    let timeouts = runs.len() - completed.len() - deaths;
+
+    println!(
+        "  completed: {:>5} ({:.1}%)",
+        completed.len(),
+        completed.len() as f32 / count * 100.0
+    );
+    println!(
+        "  died:      {:>5} ({:.1}%)",
+        deaths,
+        deaths as f32 / count * 100.0
+    );
+    println!(
+        "  timed out: {:>5} ({:.1}%) after {:.0}s",
+        timeouts,
+        timeouts as f32 / count * 100.0,
+        opts.max_time
+    );
+
+    if !completed.is_empty() {
+        let times: Vec<f32> = completed.iter().map(|r| r.time).collect();
+        let avg = times.iter().sum::<f32>() / times.len() as f32;
+        let min = times.iter().copied().fold(f32::MAX, f32::min);
+        let max = times.iter().copied().fold(0.0, f32::max);
+        println!("  time to complete: avg {avg:.1}s  min {min:.1}s  max {max:.1}s");
+    }
+
+    let avg_damage = runs.iter().map(|r| r.combat.damage_taken).sum::<f32>() / count;
+    let avg_kills = runs.iter().map(|r| r.combat.kills as f32).sum::<f32>() / count;
+    println!("  damage taken: avg {avg_damage:.1} HP per run");
+    println!("  kills: avg {avg_kills:.1} per run");
+
+    // Loot that actually dropped while playing, per item kind
+    let mut tally: Vec<(&str, u32)> = Vec::new();
+    for item in runs.iter().flat_map(|r| &r.combat.loot_dropped) {
+        let name = loot_name(item);
+        match tally.iter_mut().find(|(n, _)| *n == name) {
+            Some(entry) => entry.1 += 1,
+            None => tally.push((name, 1)),
+        }
+    }
+    let drops: u32 = tally.iter().map(|(_, n)| n).sum();
+    println!(
+        "  loot dropped: avg {:.1} items per run",
+        drops as f32 / count
+    );
+    for (name, n) in tally {
+        let share = n as f32 / drops as f32 * 100.0;
+        println!("    {name:<12} {share:>5.1}%");
+    }
+}
+
+fn report_loot(opts: &Options, data: &GameData) {
+    println!(
+        "\nLoot distribution on {} ({} rolls per source)",
+        difficulty_name(opts.difficulty),
+        opts.loot_rolls
+    );
+
+    let rng = RandGenerator::new();
+    rng.srand(opts.seed);
+    let rolls = opts.loot_rolls.max(1);
+    let sources = [
+        ("Asteroid", LootSource::Asteroid),
+        ("Rare asteroid", LootSource::RareAsteroid),
+        ("Enemy", LootSource::EnemySmall),
+    ];
+
+    for (name, source) in sources {
+        // (name, drops, total amount)
+        let mut tally: Vec<(&str, u32, u32)> = Vec::new();
+        let mut nothing = 0;
+        for _ in 0..rolls {
+            let Some(loot) = generate_loot(Vec2::ZERO, source, opts.difficulty, &data.loot, &rng)
+            else {
+                nothing += 1;
+                continue;
+            };
+            let item = loot_name(&loot.item_type);
+            let amount = loot_amount(&loot.item_type).unwrap_or(0);
+            match tally.iter_mut().find(|(n, _, _)| *n == item) {
+                Some(entry) => {
+                    entry.1 += 1;
+                    entry.2 += amount;
+                }
+                None => tally.push((item, 1, amount)),
+            }
+        }
+
+        println!("  {name}:");
+        for (item, drops, amount) in tally {
+            let share = drops as f32 / rolls as f32 * 100.0;
+            if amount > 0 {
+                let avg = amount as f32 / drops as f32;
+                println!("    {item:<12} {share:>5.1}%  avg amount {avg:.1}");
+            } else {
+                println!("    {item:<12} {share:>5.1}%");
+            }
+        }
+        if nothing > 0 {
+            let share = nothing as f32 / rolls as f32 * 100.0;
+            println!("    {:<12} {share:>5.1}%", "Nothing");
+        }
+    }
+}
+
+fn random_point(arena: Vec2, rng: &RandGenerator) -> Vec2 {
+    vec2(rng.gen_range(0.0, arena.x), rng.gen_range(0.0, arena.y))
+}
+
+fn random_bullet(style: BulletStyle, arena: Vec2, rng: &RandGenerator) -> Bullet {
+    let pos = random_point(arena, rng);
+    let angle = rng.gen_range(0.0, std::f32::consts::TAU);
+    Bullet {
+        pos,
+        prev_pos: pos,
+        vel: vec2(angle.cos(), angle.sin()) * 300.0,
+        life_time: 2.0,
+        style,
+        damage: 15.0,
+        radius: 6.0,
+    }
+}
+
+// Keeps the arena packed with `entities` bullets, asteroids and enemies and times
+// every simulation tick, to check the collision pass against the frame budget
+fn run_stress(opts: &Options, data: GameData, entities: usize) {
+    let rng = RandGenerator::new();
+    rng.srand(opts.seed);
+    let mut game = Game::new(opts.arena, opts.seed, data);
+    game.difficulty = opts.difficulty;
+    game.reset(opts.seed);
+    game.start_mission();
+
+    let asteroids = entities / 4;
+    let enemies = entities / 8;
+    let bullets = entities - asteroids - enemies;
+    let idle = PlayerInput::default();
+    let mut tick_times = Vec::with_capacity(STRESS_TICKS as usize);
+    let mut peak_entities = 0;
+
+    for _ in 0..STRESS_TICKS {
+        // Top everything back up; the previous tick destroyed some of it
+        while game.asteroids.len() < asteroids {
+            let mut asteroid = Asteroid::new_large(opts.arena, &rng);
+            asteroid.radius = [10.0, 20.0, 40.0][rng.gen_range(0, 3)];
+            game.asteroids.push(asteroid);
+        }
+        while game.enemy_ships.len() < enemies {
+            let before = game.enemy_ships.len();
+            spawn_enemy(&mut game);
+            // A mix without any weight spawns nothing
+            let Some(enemy) = game.enemy_ships.get_mut(before) else {
+                break;
+            };
+            enemy.pos = random_point(opts.arena, &rng);
+            enemy.prev_pos = enemy.pos;
+        }
+        while game.bullets.len() < bullets {
+            let style = if game.bullets.len().is_multiple_of(2) {
+                BulletStyle::Player
+            } else {
+                BulletStyle::Enemy
+            };
+            game.bullets.push(random_bullet(style, opts.arena, &rng));
+        }
+        // The ship only soaks up damage here
+        game.ship.health = game.ship.max_health;
+
+        peak_entities = peak_entities.max(
+            game.asteroids.len()
+                + game.enemy_ships.len()
+                + game.bullets.len()
+                + game.loot_items.len(),
+        );
+
+        let start = Instant::now();
+        update_game(&mut game, &idle, TICK_DT);
+        tick_times.push(start.elapsed().as_secs_f64() * 1000.0);
+    }
+
+    tick_times.sort_by(f64::total_cmp);
+    let avg = tick_times.iter().sum::<f64>() / tick_times.len() as f64;
+    let p99 = tick_times[tick_times.len() * 99 / 100];
+    let max = tick_times[tick_times.len() - 1];
+    let budget = TICK_DT as f64 * 1000.0;
+    println!(
+        "Stress: {} ticks with up to {peak_entities} entities \
+         ({bullets} bullets, {asteroids} asteroids, {enemies} enemies)",
+        STRESS_TICKS
+    );
+    println!("  tick time: avg {avg:.3}ms  p99 {p99:.3}ms  max {max:.3}ms");
+    println!(
+        "  budget {budget:.1}ms per tick: {}",
+        if p99 < budget { "OK" } else { "OVER BUDGET" }
+    );
+}
+
+fn main() {
+    let opts = parse_options();
+    let data = GameData::load(&opts.data_dir).unwrap_or_else(|e| {
+        eprintln!("Failed to load game data: {e}");
+        std::process::exit(1);
+    });
+
+    if let Some(entities) = opts.stress {
+        run_stress(&opts, data, entities);
+        return;
+    }
+
+    let runs: Vec<RunStats> = (0..opts.runs)
+        .map(|i| run_mission(&opts, &data, opts.seed.wrapping_add(i as u64)))
+        .collect();
+
+    let lang = Localization::load(Path::new(LANG_DIR));
+    report_missions(&opts, &data, &lang, &runs);
+    report_loot(&opts, &data);
+}
@@ -1,12 +1,15 @@
+use crate::enemies::{EnemyArchetype, EnemyMixEntry, EnemyWeapon};
+use crate::systems::LootSource;
+use crate::upgrades::Upgrades;
 use macroquad::prelude::*;
-use macroquad::rand::gen_range;
+use macroquad::rand::RandGenerator;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone)]
 pub struct Mission {
     pub level_id: u32,
-    pub title: String,
-    pub description: String,
+    pub title_key: String, // localization keys, see missions::mission_text
+    pub description_key: String,
 
     // mission objectives
     pub target_kills: u32,      // how many enemies to destroy
@@ -16,6 +19,7 @@ pub struct Mission {
     // level difficulty settings
     pub enemy_spawn_interval: f32,
     pub asteroid_count: usize,
+    pub enemy_mix: Vec<EnemyMixEntry>, // weighted archetypes to spawn
 }
 
 pub enum GameState {
@@ -23,42 +27,120 @@ pub enum GameState {
     Briefing, // briefing screen before the mission
     Playing,
     Paused,         // game is paused
+    Replay,         // watching a recorded run
     MissionSuccess, // level completed
+    Hangar,         // upgrade shop between a mission success and the next briefing
     GameOver(u32),
+    NameEntry(u32), // the run's score made the leaderboard, the pilot types a name
+    HighScores(Difficulty), // leaderboard of the shown difficulty
+    Controls,       // key rebinding screen
 }
 
 #[derive(Clone, Copy, PartialEq)]
 pub enum MenuItem {
+    Continue, // Only offered while a campaign save exists
     Start,
+    HighScores,
     Difficulty,
     Language,
+    WindowMode,
+    ControlScheme,
+    Controls,
 }
 
 impl MenuItem {
-    pub fn next(self) -> Self {
+    // `can_continue` adds Continue to the top of the menu
+    pub fn next(self, can_continue: bool) -> Self {
         match self {
-            MenuItem::Start => MenuItem::Difficulty,
+            MenuItem::Continue => MenuItem::Start,
+            MenuItem::Start => MenuItem::HighScores,
+            MenuItem::HighScores => MenuItem::Difficulty,
             MenuItem::Difficulty => MenuItem::Language,
-            MenuItem::Language => MenuItem::Start,
+            MenuItem::Language => MenuItem::WindowMode,
+            MenuItem::WindowMode => MenuItem::ControlScheme,
+            MenuItem::ControlScheme => MenuItem::Controls,
+            MenuItem::Controls if can_continue => MenuItem::Continue,
+            MenuItem::Controls => MenuItem::Start,
         }
     }
 
-    pub fn prev(self) -> Self {
+    pub fn prev(self, can_continue: bool) -> Self {
         match self {
-            MenuItem::Start => MenuItem::Language,
-            MenuItem::Difficulty => MenuItem::Start,
+            MenuItem::Continue => MenuItem::Controls,
+            MenuItem::Start if can_continue => MenuItem::Continue,
+            MenuItem::Start => MenuItem::Controls,
+            MenuItem::HighScores => MenuItem::Start,
+            MenuItem::Difficulty => MenuItem::HighScores,
             MenuItem::Language => MenuItem::Difficulty,
+            MenuItem::WindowMode => MenuItem::Language,
+            MenuItem::ControlScheme => MenuItem::WindowMode,
+            MenuItem::Controls => MenuItem::ControlScheme,
         }
     }
 }
 
+// Selection on the rebinding screen: one row per action (see input::Action::ALL),
+// then a row that restores the default keys
+#[derive(Default)]
+pub struct RebindCursor {
+    pub row: usize,
+    pub slot: usize,     // Which of the action's keys is selected
+    pub waiting: bool,   // The next key pressed gets bound to the selected slot
+    pub wait_until: f64, // When waiting gives up without a new key
+}
+
+// Seconds the rebinding screen waits for a key. Every key can be bound, so
+// waiting cannot be cancelled from the keyboard.
+pub const REBIND_TIMEOUT: f64 = 5.0;
+
+// Save and load failures, shown at the bottom of every screen for a while.
+// Each is a localization key with the error as {error}.
+#[derive(Default)]
+pub struct Notices {
+    messages: Vec<(&'static str, String, f64)>, // Key, error, time it disappears
+}
+
+pub const NOTICE_DURATION: f64 = 8.0; // Seconds
+
+impl Notices {
+    pub fn push(&mut self, key: &'static str, error: impl std::fmt::Display) {
+        self.messages
+            .push((key, error.to_string(), get_time() + NOTICE_DURATION));
+    }
+
+    // Messages still showing, oldest first
+    pub fn current(&mut self) -> impl Iterator<Item = (&'static str, &str)> {
+        let now = get_time();
+        self.messages.retain(|(_, _, until)| *until > now);
+        self.messages
+            .iter()
+            .map(|(key, error, _)| (*key, error.as_str()))
+    }
+}
+
+// Player controls sampled for a single simulation tick
+#[derive(Clone, Copy, Default, PartialEq)]
+pub struct PlayerInput {
+    pub turn: f32,    // From -1 (full left) to 1 (full right); keys give -1, 0 or 1
+    pub thrust: f32,  // From 0 to 1; a gamepad stick can ask for part of the engine's power
+    pub reverse: f32, // From 0 to 1, for the reverse thrusters
+    pub brake: bool,  // Inertial dampeners slow the ship down
+    pub fire: bool,
+    pub pause: bool, // Pause was requested (not used by the simulation, kept for replays)
+    // Mouse aim: heading in degrees the ship turns towards, instead of `turn`
+    pub aim: Option<f32>,
+    // Screen direction in degrees to thrust in; None thrusts where the ship faces
+    pub thrust_heading: Option<f32>,
+}
+
 #[derive(Clone, Copy, PartialEq)]
 pub enum BulletStyle {
     Player,
     Enemy,
 }
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum EnemyType {
     Regular,  // Can shoot
     Kamikaze, // Flies to player and explodes
@@ -66,6 +148,7 @@ pub enum EnemyType {
 
 pub struct Bullet {
     pub pos: Vec2,
+    pub prev_pos: Vec2, // Position at the start of the tick (for render interpolation)
     pub vel: Vec2,
     pub life_time: f32,
     pub style: BulletStyle,
@@ -75,6 +158,7 @@ pub struct Bullet {
 
 pub struct Asteroid {
     pub pos: Vec2,
+    pub prev_pos: Vec2, // Position at the start of the tick (for render interpolation)
     pub vel: Vec2,
     pub radius: f32,
     pub is_rare: bool,
@@ -82,26 +166,39 @@ pub struct Asteroid {
 
 pub struct EnemyShip {
     pub pos: Vec2,
+    pub prev_pos: Vec2, // Position at the start of the tick (for render interpolation)
     pub vel: Vec2,
     pub shoot_timer: f32,
     pub rotation: f32,
     pub health: f32,           // Current health points
     pub max_health: f32,       // Maximum health points
     pub enemy_type: EnemyType, // Type of enemy (Regular or Kamikaze)
+    pub archetype: usize,      // Index into the enemy archetypes
+    pub speed: f32,
+    pub weapon: Option<EnemyWeapon>,
+    pub ram_damage: f32, // Dealt when a kamikaze hits the ship
+    pub size: f32,
+    pub radius: f32, // Collision radius
+    pub loot: LootSource,
 }
 
 pub struct Ship {
     pub pos: Vec2,
+    pub prev_pos: Vec2, // Position at the start of the tick (for render interpolation)
     pub vel: Vec2,
     pub rotation: f32,
+    pub prev_rotation: f32,
     pub health: f32,     // Current health points
     pub max_health: f32, // Maximum health points
     pub shoot_timer: f32,
     pub rapid_fire_timer: f32,
     pub engine: Engine,
+    pub reverse_engine: Engine, // Nozzles at the nose that push the ship backwards
+    pub max_speed: f32,         // The engines cannot push the ship faster than this
 
-    pub scrap: u32,      // Ordinary money
-    pub rare_metal: u32, // Premium money
+    pub scrap: u32,         // Ordinary money
+    pub rare_metal: u32,    // Premium money
+    pub upgrades: Upgrades, // Bought in the Hangar, see Game::apply_upgrades
 
     // Shield state
     pub shield_hp: f32,     // Current shield HP (0 if inactive)
@@ -117,10 +214,11 @@ pub struct Engine {
     pub ramp_up: f32,        // Speed of thrust increase
     pub decay: f32,          // Speed of decay
     pub offset: f32,         // Offset of the nozzles relative to the center of the ship
+    pub flame_scale: f32,    // Size of the flame at full thrust, relative to the main engine
 }
 
 // 1. Types of loot
-#[derive(Clone, PartialEq)]
+#[derive(Clone, PartialEq, Debug)]
 pub enum LootType {
     // Currencies
     Scrap(u32),     // Scrap (ordinary resource)
@@ -136,7 +234,8 @@ pub enum LootType {
 // 2. The entity of the dropped item
 pub struct LootItem {
     pub pos: Vec2,
-    pub vel: Vec2,       // Initial explosion velocity (decays)
+    pub prev_pos: Vec2, // Position at the start of the tick (for render interpolation)
+    pub vel: Vec2,      // Initial explosion velocity (decays)
     pub drift_vel: Vec2, // Slow constant drift in space
     pub item_type: LootType,
     pub radius: f32,
@@ -145,12 +244,62 @@ pub struct LootItem {
     pub rotation_speed: f32, // Rotation speed in radians per second (can be negative)
 }
 
+// Runs kept on each difficulty's leaderboard
+pub const LEADERBOARD_SIZE: usize = 10;
+
+// Bump when the leaderboard format changes, and migrate older files in systems::load_score.
+// Files without a version are version 1: either the leaderboards or, from before them,
+// a single `high_score`.
+pub const SAVE_DATA_VERSION: u32 = 2;
+
+// Best runs per difficulty, highest score first
 #[derive(Serialize, Deserialize, Debug)]
 pub struct SaveData {
-    pub high_score: u32,
+    pub version: u32,
+    #[serde(default)]
+    pub nebula: Vec<ScoreEntry>,
+    #[serde(default)]
+    pub supernova: Vec<ScoreEntry>,
+    #[serde(default)]
+    pub black_hole: Vec<ScoreEntry>,
 }
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ScoreEntry {
+    pub name: String,
+    pub score: u32,
+    pub level: u32,   // Mission the run ended on, 0 if unknown
+    pub date: String, // YYYY-MM-DD, UTC, empty if unknown
+}
+
+// Name recorded when the pilot leaves the name entry empty
+pub const DEFAULT_PILOT_NAME: &str = "PILOT";
+// Longest name that fits the leaderboard's name column
+pub const PILOT_NAME_MAX_LEN: usize = 10;
+
+// Bump when the campaign save format changes, and migrate older saves in systems::load_campaign
+pub const CAMPAIGN_VERSION: u32 = 2;
+
+// Progress of a campaign as it stood on the last briefing screen
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CampaignSave {
+    pub version: u32,
+    pub seed: u64,
+    pub difficulty: Difficulty,
+    pub level: u32,
+    pub score: u32,
+    pub scrap: u32,
+    pub rare_metal: u32,
+    pub shield_hp: f32,
+    pub shield_max_hp: f32,
+    pub shield_timer: f32,
+    pub rapid_fire_timer: f32,
+    pub big_bullet_timer: f32,
+    #[serde(default)] // Version 1 had no upgrades
+    pub upgrades: Upgrades,
+}
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, Debug)]
 pub enum Difficulty {
     Nebula,    // Easy
     Supernova, // Medium
@@ -158,25 +307,25 @@ pub enum Difficulty {
 }
 
 impl Asteroid {
-    pub fn new_large() -> Self {
+    pub fn new_large(arena: Vec2, rng: &RandGenerator) -> Self {
         // 10% chance of being rare
-        let is_rare = gen_range(0, 100) < 10;
+        let is_rare = rng.gen_range(0, 100) < 10;
+        let pos = vec2(rng.gen_range(0.0, arena.x), rng.gen_range(0.0, arena.y));
         Self {
-            pos: vec2(
-                gen_range(0.0, screen_width()),
-                gen_range(0.0, screen_height()),
-            ),
-            vel: vec2(gen_range(-80.0, 80.0), gen_range(-80.0, 80.0)),
+            pos,
+            prev_pos: pos,
+            vel: vec2(rng.gen_range(-80.0, 80.0), rng.gen_range(-80.0, 80.0)),
             radius: 40.0,
             is_rare,
         }
     }
 
-    pub fn new_fragment(pos: Vec2, radius: f32) -> Self {
+    pub fn new_fragment(pos: Vec2, radius: f32, rng: &RandGenerator) -> Self {
         // Fragments are never rare
         Self {
             pos,
-            vel: vec2(gen_range(-120.0, 120.0), gen_range(-120.0, 120.0)),
+            prev_pos: pos,
+            vel: vec2(rng.gen_range(-120.0, 120.0), rng.gen_range(-120.0, 120.0)),
             radius: radius / 2.0,
             is_rare: false,
         }
@@ -184,36 +333,38 @@ impl Asteroid {
 }
 
 impl EnemyShip {
-    pub fn new() -> Self {
-        let side = gen_range(0, 2);
-        let x = if side == 0 {
-            -30.0
-        } else {
-            screen_width() + 30.0
-        };
-        let y = gen_range(50.0, screen_height() - 50.0);
-
-        // Randomly choose enemy type (70% regular, 30% kamikaze)
-        let enemy_type = if gen_range(0, 100) < 30 {
-            EnemyType::Kamikaze
+    pub fn new(
+        arena: Vec2,
+        archetype_idx: usize,
+        archetype: &EnemyArchetype,
+        rng: &RandGenerator,
+    ) -> Self {
+        let side = rng.gen_range(0, 2);
+        let x = if side == 0 { -30.0 } else { arena.x + 30.0 };
+        let y = rng.gen_range(50.0, arena.y - 50.0);
+
+        let speed_x = if side == 0 {
+            archetype.speed
         } else {
-            EnemyType::Regular
-        };
-
-        let speed_x = if side == 0 { 120.0 } else { -120.0 };
-        let max_health = match enemy_type {
-            EnemyType::Regular => 24.0,
-            EnemyType::Kamikaze => 18.0, // Slightly less health for kamikaze
+            -archetype.speed
         };
 
         Self {
             pos: vec2(x, y),
-            vel: vec2(speed_x, gen_range(-20.0, 20.0)),
-            shoot_timer: 1.5,
+            prev_pos: vec2(x, y),
+            vel: vec2(speed_x, rng.gen_range(-20.0, 20.0)),
+            shoot_timer: archetype.weapon.map_or(0.0, |w| w.first_shot),
             rotation: 0.0,
-            health: max_health,
-            max_health,
-            enemy_type,
+            health: archetype.health,
+            max_health: archetype.health,
+            enemy_type: archetype.behaviour,
+            archetype: archetype_idx,
+            speed: archetype.speed,
+            weapon: archetype.weapon,
+            ram_damage: archetype.ram_damage.unwrap_or(0.0),
+            size: archetype.size,
+            radius: archetype.collision_radius,
+            loot: archetype.loot,
         }
     }
 
@@ -227,7 +378,7 @@ impl EnemyShip {
 impl Ship {
     // Returns true if the game is over
     // Damage is first applied to shield if active, then to ship health
-    pub fn take_damage(&mut self, damage: f32, score: u32) -> bool {
+    pub fn take_damage(&mut self, damage: f32) -> bool {
         // If shield is active, absorb damage with shield first
         if self.shield_hp > 0.0 {
             if self.shield_hp >= damage {
@@ -246,13 +397,7 @@ impl Ship {
             self.health -= damage;
         }
 
-        if self.health <= 0.0 {
-            // Save score immediately using our system
-            crate::systems::save_score(score);
-            true // Game Over
-        } else {
-            false // Still alive
-        }
+        self.health <= 0.0 // Game Over when health is gone
     }
 
     // Restore health (used by health packs)
@@ -280,15 +425,29 @@ impl Engine {
             ramp_up: 5.0,
             decay: 3.0,
             offset: 42.0,
+            flame_scale: 1.0,
         }
     }
 
-    // All the logic of changing the thrust is now encapsulated here
-    pub fn update(&mut self, dt: f32, is_active: bool) {
-        if is_active {
-            self.current_thrust = (self.current_thrust + self.ramp_up * dt).min(1.0);
+    // Smaller thrusters at the nose, drawn pointing forward
+    pub fn reverse() -> Self {
+        Self {
+            current_thrust: 0.0,
+            ramp_up: 6.0,
+            decay: 6.0,
+            offset: 30.0,
+            flame_scale: 0.5,
+        }
+    }
+
+    // All the logic of changing the thrust is now encapsulated here.
+    // The thrust ramps up or decays towards the requested `throttle` (0 to 1).
+    pub fn update(&mut self, dt: f32, throttle: f32) {
+        let throttle = throttle.clamp(0.0, 1.0);
+        if self.current_thrust < throttle {
+            self.current_thrust = (self.current_thrust + self.ramp_up * dt).min(throttle);
         } else {
-            self.current_thrust = (self.current_thrust - self.decay * dt).max(0.0);
+            self.current_thrust = (self.current_thrust - self.decay * dt).max(throttle);
         }
     }
 }
@@ -302,9 +461,62 @@ pub struct Explosion {
     pub scale: f32,        // Explosion size (for boss large, for enemy small)
 }
 
+impl Default for SaveData {
+    fn default() -> Self {
+        Self {
+            version: SAVE_DATA_VERSION,
+            nebula: Vec::new(),
+            supernova: Vec::new(),
+            black_hole: Vec::new(),
+        }
+    }
+}
+
 impl SaveData {
     pub fn new() -> Self {
-        Self { high_score: 0 }
+        Self::default()
+    }
+
+    pub fn board(&self, difficulty: Difficulty) -> &[ScoreEntry] {
+        match difficulty {
+            Difficulty::Nebula => &self.nebula,
+            Difficulty::Supernova => &self.supernova,
+            Difficulty::BlackHole => &self.black_hole,
+        }
+    }
+
+    fn board_mut(&mut self, difficulty: Difficulty) -> &mut Vec<ScoreEntry> {
+        match difficulty {
+            Difficulty::Nebula => &mut self.nebula,
+            Difficulty::Supernova => &mut self.supernova,
+            Difficulty::BlackHole => &mut self.black_hole,
+        }
+    }
+
+    pub fn high_score(&self, difficulty: Difficulty) -> u32 {
+        self.board(difficulty)
+            .first()
+            .map_or(0, |entry| entry.score)
+    }
+
+    // Whether a run with this score would make it onto the leaderboard
+    pub fn qualifies(&self, difficulty: Difficulty, score: u32) -> bool {
+        let board = self.board(difficulty);
+        score > 0
+            && (board.len() < LEADERBOARD_SIZE || board.last().is_some_and(|e| score > e.score))
+    }
+
+    // Inserts a run below any equal scores and returns its place (0 is the top),
+    // None if it did not qualify
+    pub fn add(&mut self, difficulty: Difficulty, entry: ScoreEntry) -> Option<usize> {
+        if !self.qualifies(difficulty, entry.score) {
+            return None;
+        }
+        let board = self.board_mut(difficulty);
+        let rank = board.partition_point(|e| e.score >= entry.score);
+        board.insert(rank, entry);
+        board.truncate(LEADERBOARD_SIZE);
+        Some(rank)
     }
 }
 
@@ -341,13 +553,37 @@ impl Difficulty {
         }
     }
 
-    // Loot chance (Bonus to roll)
-    // Negative means less chances
-    pub fn loot_luck_modifier(&self) -> i32 {
+    pub fn next(self) -> Self {
+        match self {
+            Difficulty::Nebula => Difficulty::Supernova,
+            Difficulty::Supernova => Difficulty::BlackHole,
+            Difficulty::BlackHole => Difficulty::Nebula,
+        }
+    }
+
+    pub fn prev(self) -> Self {
+        match self {
+            Difficulty::Nebula => Difficulty::BlackHole,
+            Difficulty::Supernova => Difficulty::Nebula,
+            Difficulty::BlackHole => Difficulty::Supernova,
+        }
+    }
+
+    // Localization key of the difficulty's name
+    pub fn name_key(self) -> &'static str {
+        match self {
+            Difficulty::Nebula => "diff_nebula",
+            Difficulty::Supernova => "diff_supernova",
+            Difficulty::BlackHole => "diff_blackhole",
+        }
+    }
+
+    // Color the difficulty is shown in
+    pub fn color(self) -> Color {
         match self {
-            Difficulty::Nebula => 10, // +10% to luck
-            Difficulty::Supernova => 0,
-            Difficulty::BlackHole => -15, // -15% to luck
+            Difficulty::Nebula => GREEN,
+            Difficulty::Supernova => YELLOW,
+            Difficulty::BlackHole => RED,
         }
     }
 }
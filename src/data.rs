@@ -0,0 +1,123 @@
+use crate::enemies::EnemySet;
+use crate::loot::LootTables;
+use crate::missions::MissionSet;
+use crate::tuning::Tuning;
+use crate::upgrades::UpgradeSet;
+use serde::de::DeserializeOwned;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Default location of the gameplay data files, relative to the working directory
+pub const DATA_DIR: &str = "assets/data";
+// Watched for changes while the game runs
+pub const TUNING_FILE: &str = "tuning.json";
+
+// Gameplay content that designers edit without recompiling
+#[derive(Clone)]
+pub struct GameData {
+    pub missions: MissionSet,
+    pub loot: LootTables,
+    pub enemies: EnemySet,
+    pub tuning: Tuning,
+    pub upgrades: UpgradeSet,
+}
+
+#[derive(Debug)]
+pub enum DataError {
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    Parse {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+    Texture {
+        path: PathBuf,
+        source: macroquad::Error,
+    },
+    // The file parsed, but its content makes no sense
+    Invalid {
+        path: PathBuf,
+        problems: Vec<String>,
+    },
+}
+
+impl fmt::Display for DataError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DataError::Io { path, source } => {
+                write!(f, "cannot read {}: {source}", path.display())
+            }
+            DataError::Parse { path, source } => {
+                write!(f, "{} is not valid: {source}", path.display())
+            }
+            DataError::Texture { path, source } => {
+                write!(f, "cannot load texture {}: {source}", path.display())
+            }
+            DataError::Invalid { path, problems } => {
+                write!(f, "{} has {} problem(s):", path.display(), problems.len())?;
+                for problem in problems {
+                    write!(f, "\n  - {problem}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for DataError {}
+
+impl GameData {
+    pub fn load(dir: &Path) -> Result<Self, DataError> {
+        let missions_path = dir.join("missions.json");
+        let missions: MissionSet = load_validated(&missions_path)?;
+        let enemies: EnemySet = load_validated(&dir.join("enemies.json"))?;
+        check(&missions_path, missions.validate_enemies(&enemies))?;
+
+        Ok(Self {
+            missions,
+            loot: load_validated(&dir.join("loot.json"))?,
+            enemies,
+            tuning: load_validated(&dir.join(TUNING_FILE))?,
+            upgrades: load_validated(&dir.join("upgrades.json"))?,
+        })
+    }
+}
+
+pub fn load_json<T: DeserializeOwned>(path: &Path) -> Result<T, DataError> {
+    let content = fs::read_to_string(path).map_err(|source| DataError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    serde_json::from_str(&content).map_err(|source| DataError::Parse {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+// Data files with rules beyond what parsing checks
+pub trait Validate {
+    // Returns a human readable description of every problem found
+    fn validate(&self) -> Vec<String>;
+}
+
+// Parses a data file and rejects it with every problem its validation finds
+pub fn load_validated<T: DeserializeOwned + Validate>(path: &Path) -> Result<T, DataError> {
+    let value: T = load_json(path)?;
+    check(path, value.validate())?;
+    Ok(value)
+}
+
+// Turns a list of validation problems into an error for `path`
+pub fn check(path: &Path, problems: Vec<String>) -> Result<(), DataError> {
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(DataError::Invalid {
+            path: path.to_path_buf(),
+            problems,
+        })
+    }
+}
@@ -3,48 +3,41 @@ use crate::resources::Resources;
 use macroquad::prelude::*;
 use macroquad::rand::gen_range;
 
+// Draws text centered on screen, one line per `\n`
 pub fn draw_text_centered(text: &str, y_offset: f32, size: u16, color: Color, res: &Resources) {
-    let font = res.font.as_ref();
-    let params = TextParams {
-        font,
-        font_size: size,
-        color,
-        ..Default::default()
-    };
-
-    let dims = measure_text(text, res.font.as_ref(), size, 1.0);
-    draw_text_ex(
-        text,
-        screen_width() / 2.0 - dims.width / 2.0,
-        screen_height() / 2.0 - dims.height / 2.0 + y_offset,
-        params,
-    );
+    let lang = res.lang.current_lang;
+    let size = size as f32;
+    for (i, line) in text.lines().enumerate() {
+        let dims = res.fonts.measure(lang, line, size);
+        res.fonts.draw(
+            lang,
+            line,
+            screen_width() / 2.0 - dims.width / 2.0,
+            screen_height() / 2.0 - dims.height / 2.0 + y_offset + i as f32 * size * 1.5,
+            size,
+            color,
+        );
+    }
 }
 
 pub fn draw_text_with_font(text: &str, x: f32, y: f32, size: f32, color: Color, res: &Resources) {
-    let font = res.font.as_ref();
-    // Normalize font size - when font doesn't support Cyrillic, fallback fonts
-    // may render larger. We use font_scale to normalize the rendering.
-    let font_size = size as u16;
-
-    // Check if text contains Cyrillic characters and adjust scale if needed
-    let has_cyrillic = text.chars().any(|c| matches!(c, '\u{0400}'..='\u{04FF}'));
-    let font_scale = if has_cyrillic {
-        // Fallback font for Cyrillic renders larger, scale down to match other languages
-        // Adjust this value if still not matching (lower = smaller)
-        0.72
-    } else {
-        1.0
-    };
+    res.fonts
+        .draw(res.lang.current_lang, text, x, y, size, color);
+}
 
-    let params = TextParams {
-        font,
-        font_size,
-        color,
-        font_scale,
-        ..Default::default()
-    };
-    draw_text_ex(text, x, y, params);
+// Maps the arena onto the window, scaled to fit and centered. The arena is taken
+// from the window when a mission starts, but the window can be resized since and a
+// replay keeps the arena it was recorded in.
+pub fn arena_camera(arena: Vec2) -> Camera2D {
+    let screen = vec2(screen_width(), screen_height());
+    let scale = (screen.x / arena.x).min(screen.y / arena.y);
+    let view = screen / scale;
+    Camera2D::from_display_rect(Rect::new(
+        (arena.x - view.x) / 2.0,
+        (arena.y - view.y) / 2.0,
+        view.x,
+        view.y,
+    ))
 }
 
 pub fn draw_background(texture: &Texture2D) {
@@ -78,22 +71,33 @@ pub fn draw_background(texture: &Texture2D) {
     );
 }
 
+// `pos` and `rotation` are passed separately from the ship so the caller can
+// draw it at an interpolated point between simulation ticks
 pub fn draw_ship(
     ship: &Ship,
+    pos: Vec2,
+    rotation: f32,
     body_tex: &Texture2D,
     flame_tex: &Texture2D,
     shield_tex: Option<&Texture2D>,
 ) {
-    let r_rad = ship.rotation.to_radians();
-
-    draw_engine(&ship.engine, ship.pos, r_rad, flame_tex);
+    let r_rad = rotation.to_radians();
+
+    draw_engine(&ship.engine, pos, r_rad, flame_tex);
+    // Turned around, so its flame comes out of the nose
+    draw_engine(
+        &ship.reverse_engine,
+        pos,
+        r_rad + std::f32::consts::PI,
+        flame_tex,
+    );
 
     let ship_size = 72.0;
 
     draw_texture_ex(
         body_tex,
-        ship.pos.x - ship_size / 2.0,
-        ship.pos.y - ship_size / 2.0,
+        pos.x - ship_size / 2.0,
+        pos.y - ship_size / 2.0,
         WHITE,
         DrawTextureParams {
             dest_size: Some(vec2(ship_size, ship_size)),
@@ -115,8 +119,8 @@ pub fn draw_ship(
 
             draw_texture_ex(
                 shield_texture,
-                ship.pos.x - shield_size / 2.0,
-                ship.pos.y - shield_size / 2.0,
+                pos.x - shield_size / 2.0,
+                pos.y - shield_size / 2.0,
                 shield_color,
                 DrawTextureParams {
                     dest_size: Some(vec2(shield_size, shield_size)),
@@ -134,8 +138,8 @@ pub fn draw_engine(engine: &Engine, ship_pos: Vec2, ship_rotation_rad: f32, text
 
     let dir_vec = vec2(ship_rotation_rad.cos(), ship_rotation_rad.sin());
 
-    let max_flame_w = 22.0;
-    let max_flame_h = 52.0;
+    let max_flame_w = 22.0 * engine.flame_scale;
+    let max_flame_h = 52.0 * engine.flame_scale;
 
     let current_w = max_flame_w * engine.current_thrust;
     let flicker = gen_range(-3.0, 3.0) * engine.current_thrust; // bigger flicker
@@ -156,19 +160,13 @@ pub fn draw_engine(engine: &Engine, ship_pos: Vec2, ship_rotation_rad: f32, text
     );
 }
 
-pub fn draw_enemy(enemy: &EnemyShip, res: &Resources) {
-    let size = match enemy.enemy_type {
-        crate::components::EnemyType::Regular => vec2(60.0, 60.0),
-        crate::components::EnemyType::Kamikaze => vec2(45.0, 45.0), // Smaller kamikaze
-    };
-    let texture = match enemy.enemy_type {
-        crate::components::EnemyType::Regular => &res.enemy_small,
-        crate::components::EnemyType::Kamikaze => &res.enemy_kamikaze,
-    };
+pub fn draw_enemy(enemy: &EnemyShip, pos: Vec2, res: &Resources) {
+    let size = vec2(enemy.size, enemy.size);
+    let texture = &res.enemy_sprites[enemy.archetype];
     draw_texture_ex(
         texture,
-        enemy.pos.x - size.x / 2.0,
-        enemy.pos.y - size.y / 2.0,
+        pos.x - size.x / 2.0,
+        pos.y - size.y / 2.0,
         WHITE,
         DrawTextureParams {
             dest_size: Some(size),
@@ -178,7 +176,7 @@ pub fn draw_enemy(enemy: &EnemyShip, res: &Resources) {
     );
 }
 
-pub fn draw_loot(item: &LootItem, res: &Resources) {
+pub fn draw_loot(item: &LootItem, pos: Vec2, res: &Resources) {
     let texture = match item.item_type {
         LootType::Scrap(_) => &res.loot_scrap,
         LootType::RareMetal(_) => &res.loot_rare,
@@ -192,8 +190,8 @@ pub fn draw_loot(item: &LootItem, res: &Resources) {
     let size = vec2(item.radius * 4.5, item.radius * 4.5);
     draw_texture_ex(
         texture,
-        item.pos.x - size.x / 2.0,
-        item.pos.y - size.y / 2.0,
+        pos.x - size.x / 2.0,
+        pos.y - size.y / 2.0,
         WHITE,
         DrawTextureParams {
             dest_size: Some(size),
@@ -203,7 +201,7 @@ pub fn draw_loot(item: &LootItem, res: &Resources) {
     );
 }
 
-pub fn draw_asteroid(asteroid: &Asteroid, res: &Resources) {
+pub fn draw_asteroid(asteroid: &Asteroid, pos: Vec2, res: &Resources) {
     let texture = if asteroid.is_rare {
         &res.rare_asteroid
     } else {
@@ -213,8 +211,8 @@ pub fn draw_asteroid(asteroid: &Asteroid, res: &Resources) {
     let size = vec2(asteroid.radius * 2.0, asteroid.radius * 2.0);
     draw_texture_ex(
         texture,
-        asteroid.pos.x - size.x / 2.0,
-        asteroid.pos.y - size.y / 2.0,
+        pos.x - size.x / 2.0,
+        pos.y - size.y / 2.0,
         WHITE,
         DrawTextureParams {
             dest_size: Some(size),
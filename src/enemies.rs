@@ -0,0 +1,144 @@
+use crate::components::EnemyType;
+use crate::data::Validate;
+use crate::systems::LootSource;
+use macroquad::rand::RandGenerator;
+use serde::Deserialize;
+
+// Enemy archetypes from enemies.json. Missions refer to them by name in their
+// `enemies` mix; ships keep the index of their archetype (see Resources::enemy_sprites).
+#[derive(Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct EnemySet {
+    pub archetypes: Vec<EnemyArchetype>,
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct EnemyArchetype {
+    pub name: String,
+    pub health: f32,
+    pub speed: f32, // Flight speed for regular enemies, chase speed for kamikazes
+    pub behaviour: EnemyType,
+    #[serde(default)]
+    pub weapon: Option<EnemyWeapon>,
+    #[serde(default)]
+    pub ram_damage: Option<f32>, // Kamikazes only, before the difficulty multiplier
+    pub sprite: String, // Texture path, relative to the working directory; checked when Resources loads it
+    pub size: f32,      // Drawn size in pixels
+    pub collision_radius: f32,
+    pub loot: LootSource,
+}
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(deny_unknown_fields)]
+pub struct EnemyWeapon {
+    pub first_shot: f32, // Delay before the first shot after spawning
+    pub interval: f32,
+    pub bullet_speed: f32,
+    pub bullet_lifetime: f32,
+    pub bullet_radius: f32,
+    pub damage: f32, // Before the difficulty multiplier
+}
+
+// One entry of a mission's enemy mix
+#[derive(Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct EnemyMixEntry {
+    pub archetype: String,
+    pub weight: u32,
+}
+
+impl EnemySet {
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.archetypes.iter().position(|a| a.name == name)
+    }
+
+    // Picks an archetype index from a weighted mix
+    pub fn pick(&self, mix: &[EnemyMixEntry], rng: &RandGenerator) -> Option<usize> {
+        let total: u32 = mix.iter().map(|entry| entry.weight).sum();
+        if total == 0 {
+            return None;
+        }
+        let mut roll = rng.gen_range(0, total);
+        let entry = mix.iter().find(|entry| {
+            if roll < entry.weight {
+                true
+            } else {
+                roll -= entry.weight;
+                false
+            }
+        })?;
+        self.find(&entry.archetype)
+    }
+}
+
+impl Validate for EnemySet {
+    fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        if self.archetypes.is_empty() {
+            problems.push("`archetypes` must contain at least one archetype".to_string());
+        }
+        for (i, a) in self.archetypes.iter().enumerate() {
+            let name = format!("archetypes[{i}] (\"{}\")", a.name);
+            if a.name.trim().is_empty() {
+                problems.push(format!("{name}: `name` is empty"));
+            }
+            if self.archetypes[..i]
+                .iter()
+                .any(|other| other.name == a.name)
+            {
+                problems.push(format!("{name}: `name` is used by another archetype"));
+            }
+            for (field, value) in [
+                ("health", a.health),
+                ("speed", a.speed),
+                ("size", a.size),
+                ("collision_radius", a.collision_radius),
+            ] {
+                if value <= 0.0 {
+                    problems.push(format!(
+                        "{name}: `{field}` must be greater than 0, got {value}"
+                    ));
+                }
+            }
+            if let Some(weapon) = &a.weapon {
+                for (field, value) in [
+                    ("interval", weapon.interval),
+                    ("bullet_speed", weapon.bullet_speed),
+                    ("bullet_lifetime", weapon.bullet_lifetime),
+                    ("bullet_radius", weapon.bullet_radius),
+                    ("damage", weapon.damage),
+                ] {
+                    if value <= 0.0 {
+                        problems.push(format!(
+                            "{name}: `weapon.{field}` must be greater than 0, got {value}"
+                        ));
+                    }
+                }
+                if weapon.first_shot < 0.0 {
+                    problems.push(format!(
+                        "{name}: `weapon.first_shot` must not be negative, got {}",
+                        weapon.first_shot
+                    ));
+                }
+            }
+            match (a.behaviour, a.ram_damage) {
+                (EnemyType::Kamikaze, None) => {
+                    problems.push(format!("{name}: kamikazes need a `ram_damage`"));
+                }
+                (EnemyType::Kamikaze, Some(damage)) if damage <= 0.0 => {
+                    problems.push(format!(
+                        "{name}: `ram_damage` must be greater than 0, got {damage}"
+                    ));
+                }
+                (EnemyType::Regular, Some(_)) => {
+                    problems.push(format!("{name}: only kamikazes have a `ram_damage`"));
+                }
+                _ => {}
+            }
+        }
+
+        problems
+    }
+}
@@ -0,0 +1,54 @@
+use crate::components::LootType;
+use crate::systems::LootSource;
+use macroquad::prelude::*;
+
+// What hurt the player
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DamageSource {
+    EnemyBullet,
+    Asteroid { radius: f32 },
+    Kamikaze,
+}
+
+// Things that happened during a simulation tick.
+//
+// The collision and loot code only reports what happened; score, mission progress,
+// explosions, loot drops and picked up loot are applied by the subscribers registered
+// on `Game` (see `Game::subscribe`). Anything outside the simulation (stats, audio,
+// achievements) subscribes the same way, with state of its own. After a tick the
+// events also stay in `Game::events` until the next tick starts.
+#[derive(Clone, Debug, PartialEq)]
+pub enum GameEvent {
+    // An enemy ship was shot down, or a kamikaze rammed the player (`rammed`)
+    EnemyDestroyed {
+        pos: Vec2,
+        max_health: f32,
+        loot: LootSource,
+        rammed: bool,
+    },
+    // A player bullet broke an asteroid; small ones leave no fragments
+    AsteroidSplit {
+        pos: Vec2,
+        radius: f32,
+        is_rare: bool,
+        fragments: u32,
+    },
+    LootSpawned {
+        pos: Vec2,
+        item: LootType,
+    },
+    LootCollected {
+        item: LootType,
+    },
+    // `amount` is after difficulty scaling and before the shield absorbs any of it
+    PlayerDamaged {
+        pos: Vec2,
+        amount: f32,
+        source: DamageSource,
+    },
+    ShieldBroken,
+    // A player bullet and an enemy bullet destroyed each other
+    BulletIntercepted {
+        pos: Vec2,
+    },
+}
@@ -0,0 +1,199 @@
+use crate::data::load_json;
+use crate::localization::Language;
+use macroquad::prelude::*;
+use std::collections::HashMap;
+use std::path::Path;
+
+// Ordered font files per language code, e.g. "ru": [pixel font, font with more glyphs]
+pub const FONTS_FILE: &str = "assets/lang/fonts.json";
+
+// A loaded font plus what is needed to decide whether it can draw a character
+struct FontFace {
+    path: String,
+    font: Font,
+    glyphs: fontdue::Font,
+    scale: f32, // Makes the face's capital letters as tall as the primary font's
+}
+
+// Fonts for every language. Each character is drawn with the first font in the
+// language's chain that has a glyph for it, and macroquad's built-in font if none does.
+pub struct FontSet {
+    faces: Vec<FontFace>,
+    chains: HashMap<Language, Vec<usize>>, // Indices into `faces`, in fallback order
+}
+
+// A piece of text drawn with a single font
+struct Run<'a> {
+    text: &'a str,
+    face: Option<usize>,
+}
+
+impl FontSet {
+    // Fonts that fail to load are logged and skipped; languages missing from the
+    // file use the English chain.
+    pub async fn load() -> Self {
+        let config: HashMap<String, Vec<String>> =
+            load_json(Path::new(FONTS_FILE)).unwrap_or_else(|e| {
+                error!("Failed to load font list: {}", e);
+                HashMap::new()
+            });
+
+        let mut set = Self {
+            faces: Vec::new(),
+            chains: HashMap::new(),
+        };
+        for lang in Language::ALL {
+            let paths = config
+                .get(lang.code())
+                .or_else(|| config.get(Language::English.code()))
+                .cloned()
+                .unwrap_or_default();
+            let mut chain = Vec::new();
+            for path in paths {
+                if let Some(idx) = set.face_index(&path).await {
+                    chain.push(idx);
+                }
+            }
+            set.chains.insert(lang, chain);
+        }
+        set.normalize();
+        set
+    }
+
+    // Loads a font file once, however many languages use it
+    async fn face_index(&mut self, path: &str) -> Option<usize> {
+        if let Some(idx) = self.faces.iter().position(|f| f.path == path) {
+            return Some(idx);
+        }
+        let loaded = match load_file(path).await {
+            Ok(bytes) => load_ttf_font_from_bytes(&bytes)
+                .map_err(|e| e.to_string())
+                .and_then(|font| {
+                    fontdue::Font::from_bytes(bytes, fontdue::FontSettings::default())
+                        .map(|glyphs| (font, glyphs))
+                        .map_err(|e| e.to_string())
+                }),
+            Err(e) => Err(e.to_string()),
+        };
+        match loaded {
+            Ok((font, glyphs)) => {
+                self.faces.push(FontFace {
+                    path: path.to_string(),
+                    font,
+                    glyphs,
+                    scale: 1.0,
+                });
+                Some(self.faces.len() - 1)
+            }
+            Err(e) => {
+                error!("Failed to load font {}: {}", path, e);
+                None
+            }
+        }
+    }
+
+    // Scales every fallback font to the cap height of the first font of the
+    // first chain that uses it, so mixed text keeps one visual size.
+    fn normalize(&mut self) {
+        let cap_height = |face: &FontFace| face.glyphs.metrics('H', 100.0).height as f32;
+        let mut done = vec![false; self.faces.len()];
+        for lang in Language::ALL {
+            let chain = &self.chains[&lang];
+            let Some(&primary) = chain.first() else {
+                continue;
+            };
+            let reference = cap_height(&self.faces[primary]);
+            for &idx in chain {
+                if done[idx] {
+                    continue;
+                }
+                let height = cap_height(&self.faces[idx]);
+                if height > 0.0 && reference > 0.0 {
+                    self.faces[idx].scale = reference / height;
+                }
+                done[idx] = true;
+            }
+        }
+    }
+
+    fn face_for(&self, chain: &[usize], c: char) -> Option<usize> {
+        chain
+            .iter()
+            .copied()
+            .find(|&idx| self.faces[idx].glyphs.lookup_glyph_index(c) != 0)
+    }
+
+    // Whether the first font of the language's chain (the pixel font) can draw `c`,
+    // for text the player types in
+    pub fn has_primary_glyph(&self, lang: Language, c: char) -> bool {
+        self.chains
+            .get(&lang)
+            .and_then(|chain| chain.first())
+            .is_some_and(|&idx| self.faces[idx].glyphs.lookup_glyph_index(c) != 0)
+    }
+
+    // Splits text into runs that can each be drawn with one font.
+    // Whitespace stays in the current run.
+    fn runs<'a>(&self, lang: Language, text: &'a str) -> Vec<Run<'a>> {
+        let chain = self.chains.get(&lang).map_or(&[][..], |c| c.as_slice());
+        let mut runs: Vec<Run> = Vec::new();
+        let mut start = 0;
+        let mut current = None;
+        for (i, c) in text.char_indices() {
+            if c.is_whitespace() && i > 0 {
+                continue;
+            }
+            let face = self.face_for(chain, c);
+            if i > 0 && face != current {
+                runs.push(Run {
+                    text: &text[start..i],
+                    face: current,
+                });
+                start = i;
+            }
+            current = face;
+        }
+        if start < text.len() {
+            runs.push(Run {
+                text: &text[start..],
+                face: current,
+            });
+        }
+        runs
+    }
+
+    fn params(&self, face: Option<usize>, size: f32, color: Color) -> TextParams<'_> {
+        let (font, scale) = match face {
+            Some(idx) => (Some(&self.faces[idx].font), self.faces[idx].scale),
+            None => (None, 1.0),
+        };
+        TextParams {
+            font,
+            font_size: size as u16,
+            font_scale: scale,
+            color,
+            ..Default::default()
+        }
+    }
+
+    // Draws a single line of text with its baseline at `y`
+    pub fn draw(&self, lang: Language, text: &str, x: f32, y: f32, size: f32, color: Color) {
+        let mut x = x;
+        for run in self.runs(lang, text) {
+            let dims = draw_text_ex(run.text, x, y, self.params(run.face, size, color));
+            x += dims.width;
+        }
+    }
+
+    pub fn measure(&self, lang: Language, text: &str, size: f32) -> TextDimensions {
+        let mut total = TextDimensions::default();
+        for run in self.runs(lang, text) {
+            let params = self.params(run.face, size, WHITE);
+            let dims = measure_text(run.text, params.font, params.font_size, params.font_scale);
+            total.width += dims.width;
+            total.height = total.height.max(dims.height);
+            total.offset_y = total.offset_y.max(dims.offset_y);
+        }
+        total
+    }
+}
@@ -1,24 +1,32 @@
 use crate::components::*;
+use crate::data::GameData;
 use crate::draw::*;
+use crate::events::{DamageSource, GameEvent};
+use crate::gamepad::Gamepads;
+use crate::input::{Action, Bindings, BINDING_SLOTS};
+use crate::localization::Arg;
+use crate::missions::mission_text;
 use crate::resources::Resources;
-use crate::systems::{generate_loot, get_mission, load_score, wrap_around};
+use crate::settings::{ControlScheme, Settings};
+use crate::spatial::SpatialGrid;
+use crate::systems::{angle_diff, generate_loot, wrap_around, LootSource};
+use crate::tuning::Tuning;
+use crate::upgrades::{Upgrade, Upgrades};
 use macroquad::prelude::*;
-use std::collections::HashSet;
+use macroquad::rand::RandGenerator;
 
 // Game constants
-pub const ROTATION_SPEED: f32 = 200.0;
-pub const ACCELERATION: f32 = 150.0;
-pub const BULLET_SPEED: f32 = 400.0;
-pub const BULLET_LIFETIME: f32 = 2.0;
-pub const SHOOT_COOLDOWN: f32 = 0.3;
-pub const PLAYER_BULLET_DAMAGE: f32 = 15.0;
-pub const PLAYER_BULLET_RADIUS: f32 = 6.0;
-pub const BIG_BULLET_DAMAGE: f32 = 30.0;
-pub const BIG_BULLET_RADIUS: f32 = 12.0;
-pub const ENEMY_BULLET_DAMAGE: f32 = 15.0;
-pub const BASE_ASTEROID_DAMAGE: f32 = 5.0;
-pub const BASE_KAMIKAZE_DAMAGE: f32 = 30.0; // Base explosion damage for kamikaze
+// Feel-tuning values (speeds, damage, magnet) live in assets/data/tuning.json
 pub const SCORE_PER_ENEMY_HP: u32 = 10;
+pub const COLLISION_CELL_SIZE: f32 = 80.0; // Broadphase cell, about the largest asteroid diameter
+pub const LOOT_VELOCITY_RETENTION: f32 = 0.95; // Share of loot velocity kept every 1/60 s
+pub const SHIP_MAX_HEALTH: f32 = 150.0; // Before upgrades
+pub const MOUSE_AIM_MIN_DISTANCE: f32 = 10.0; // Closer cursors do not turn the ship
+
+// Simulation clock
+pub const TICK_RATE: f32 = 60.0; // Simulation steps per second
+pub const TICK_DT: f32 = 1.0 / TICK_RATE;
+pub const MAX_TICKS_PER_FRAME: u32 = 5; // Beyond this, time is dropped instead of caught up
 
 pub struct Game {
     pub ship: Ship,
@@ -36,12 +44,33 @@ pub struct Game {
     pub enemy_spawn_timer: f32,
     pub difficulty: Difficulty,
     pub menu_selection: MenuItem,
+    pub arena: Vec2, // Size of the playfield the simulation wraps around
+    pub seed: u64,   // Seed of the current run, shown on the game over screen
+    pub rng: RandGenerator,
+    pub events: Vec<GameEvent>, // Events of the last tick (see events.rs)
+    pub subscribers: Vec<Box<dyn EventSubscriber>>, // Called in order for every event, see subscribe
+    pub data: GameData,
+}
+
+// Reacts to one event of a tick; may change the game and emit further events.
+// Subscribers can keep state of their own (stats, audio, achievements).
+pub trait EventSubscriber {
+    fn on_event(&mut self, game: &mut Game, event: &GameEvent);
+}
+
+// Functions and closures subscribe as they are
+impl<F: FnMut(&mut Game, &GameEvent)> EventSubscriber for F {
+    fn on_event(&mut self, game: &mut Game, event: &GameEvent) {
+        self(game, event)
+    }
 }
 
 impl Game {
-    pub fn new() -> Self {
+    pub fn new(arena: Vec2, seed: u64, data: GameData) -> Self {
+        let rng = RandGenerator::new();
+        rng.srand(seed);
         Self {
-            ship: create_ship(),
+            ship: create_ship(arena, &data.tuning),
             bullets: Vec::new(),
             asteroids: Vec::new(),
             enemy_ships: Vec::new(),
@@ -49,26 +78,40 @@ impl Game {
             explosions: Vec::new(),
             score: 0,
             current_level_idx: 1,
-            current_mission: get_mission(1),
+            current_mission: data.missions.get(1),
             mission_kills: 0,
             mission_scrap_collected: 0,
             mission_rare_metal_collected: 0,
             enemy_spawn_timer: 0.0,
             difficulty: Difficulty::Supernova,
             menu_selection: MenuItem::Start,
+            arena,
+            seed,
+            rng,
+            events: Vec::new(),
+            subscribers: DEFAULT_SUBSCRIBERS
+                .into_iter()
+                .map(|f| Box::new(f) as Box<dyn EventSubscriber>)
+                .collect(),
+            data,
         }
     }
 
-    pub fn reset(&mut self) {
+    // Starts a new run; every spawn and loot roll of the run is derived from the seed
+    pub fn reset(&mut self, seed: u64) {
         let saved_diff = self.difficulty;
+        self.seed = seed;
+        self.rng.srand(seed);
         self.bullets.clear();
-        self.asteroids = (0..5).map(|_| Asteroid::new_large()).collect();
+        self.asteroids = (0..5)
+            .map(|_| Asteroid::new_large(self.arena, &self.rng))
+            .collect();
         self.loot_items.clear();
         self.enemy_ships.clear();
         self.score = 0;
         self.current_level_idx = 1;
-        self.current_mission = get_mission(self.current_level_idx);
-        self.ship = create_ship();
+        self.current_mission = self.data.missions.get(self.current_level_idx);
+        self.ship = create_ship(self.arena, &self.data.tuning);
         self.difficulty = saved_diff;
     }
 
@@ -78,7 +121,7 @@ impl Game {
         self.loot_items.clear();
 
         self.asteroids = (0..self.current_mission.asteroid_count)
-            .map(|_| Asteroid::new_large())
+            .map(|_| Asteroid::new_large(self.arena, &self.rng))
             .collect();
 
         self.mission_kills = 0;
@@ -87,10 +130,13 @@ impl Game {
         self.enemy_spawn_timer = self.current_mission.enemy_spawn_interval;
 
         // Reset ship position and movement, restore health to full
-        self.ship.pos = vec2(screen_width() / 2.0, screen_height() / 2.0);
+        self.ship.pos = self.arena / 2.0;
+        self.ship.prev_pos = self.ship.pos;
         self.ship.vel = vec2(0.0, 0.0);
         self.ship.rotation = 0.0;
+        self.ship.prev_rotation = 0.0;
         self.ship.engine.current_thrust = 0.0;
+        self.ship.reverse_engine.current_thrust = 0.0;
         // Restore health to 100% (150 HP)
         self.ship.health = self.ship.max_health;
         // Note: scrap, rare_metal, shield state, and boost timers are preserved between missions
@@ -98,7 +144,7 @@ impl Game {
 
     pub fn next_mission(&mut self) {
         self.current_level_idx += 1;
-        self.current_mission = get_mission(self.current_level_idx);
+        self.current_mission = self.data.missions.get(self.current_level_idx);
     }
 
     pub fn is_mission_complete(&self) -> bool {
@@ -107,27 +153,112 @@ impl Game {
             && self.mission_rare_metal_collected >= self.current_mission.target_rare_metal
     }
 
-    pub fn cycle_difficulty(&mut self) {
-        self.difficulty = match self.difficulty {
-            Difficulty::Nebula => Difficulty::Supernova,
-            Difficulty::Supernova => Difficulty::BlackHole,
-            Difficulty::BlackHole => Difficulty::Nebula,
+    pub fn emit(&mut self, event: GameEvent) {
+        self.events.push(event);
+    }
+
+    // Adds a subscriber after the ones already registered. It also sees the events
+    // emitted by earlier subscribers.
+    pub fn subscribe(&mut self, subscriber: impl EventSubscriber + 'static) {
+        self.subscribers.push(Box::new(subscriber));
+    }
+
+    // Everything needed to bring the campaign back to the current briefing
+    pub fn campaign_save(&self) -> CampaignSave {
+        CampaignSave {
+            version: CAMPAIGN_VERSION,
+            seed: self.seed,
+            difficulty: self.difficulty,
+            level: self.current_level_idx,
+            score: self.score,
+            scrap: self.ship.scrap,
+            rare_metal: self.ship.rare_metal,
+            shield_hp: self.ship.shield_hp,
+            shield_max_hp: self.ship.shield_max_hp,
+            shield_timer: self.ship.shield_timer,
+            rapid_fire_timer: self.ship.rapid_fire_timer,
+            big_bullet_timer: self.ship.big_bullet_timer,
+            upgrades: self.ship.upgrades.clone(),
+        }
+    }
+
+    // Starts a fresh run from a saved campaign, ready for its briefing
+    pub fn resume_campaign(&mut self, save: &CampaignSave) {
+        self.difficulty = save.difficulty;
+        self.reset(save.seed);
+        self.score = save.score;
+        self.current_level_idx = save.level;
+        self.current_mission = self.data.missions.get(save.level);
+        self.ship.scrap = save.scrap;
+        self.ship.rare_metal = save.rare_metal;
+        self.ship.shield_hp = save.shield_hp;
+        self.ship.shield_max_hp = save.shield_max_hp;
+        self.ship.shield_timer = save.shield_timer;
+        self.ship.rapid_fire_timer = save.rapid_fire_timer;
+        self.ship.big_bullet_timer = save.big_bullet_timer;
+        self.ship.upgrades = save.upgrades.clone();
+        self.apply_upgrades();
+    }
+
+    // Total effect of the levels of `upgrade` the ship has, see upgrades.json
+    pub fn upgrade_bonus(&self, upgrade: Upgrade) -> f32 {
+        self.data.upgrades.bonus(upgrade, &self.ship.upgrades)
+    }
+
+    // Updates the ship stats that upgrades change. Fire rate, damage, magnet and
+    // shields read their bonus when they are used.
+    pub fn apply_upgrades(&mut self) {
+        self.ship.max_health = SHIP_MAX_HEALTH + self.upgrade_bonus(Upgrade::MaxHealth);
+        self.ship.health = self.ship.health.min(self.ship.max_health);
+        self.ship.engine.ramp_up =
+            Engine::basic().ramp_up + self.upgrade_bonus(Upgrade::EngineRampUp);
+    }
+
+    // Buys the next level of an upgrade. Returns false if it is maxed out or
+    // the pilot cannot pay for it.
+    pub fn buy_upgrade(&mut self, upgrade: Upgrade) -> bool {
+        let Some(cost) = self.data.upgrades.next_cost(upgrade, &self.ship.upgrades) else {
+            return false;
         };
+        if self.ship.scrap < cost.scrap || self.ship.rare_metal < cost.rare_metal {
+            return false;
+        }
+        self.ship.scrap -= cost.scrap;
+        self.ship.rare_metal -= cost.rare_metal;
+        self.ship.upgrades.raise(upgrade);
+        self.apply_upgrades();
+        true
+    }
+
+    // New tuning values, from a reload of tuning.json or a replay. The ship takes
+    // its top speed from them.
+    pub fn set_tuning(&mut self, tuning: Tuning) {
+        self.ship.max_speed = tuning.max_speed;
+        self.data.tuning = tuning;
+    }
+
+    pub fn cycle_difficulty(&mut self) {
+        self.difficulty = self.difficulty.next();
     }
 }
 
-pub fn create_ship() -> Ship {
+pub fn create_ship(arena: Vec2, tuning: &Tuning) -> Ship {
     Ship {
-        pos: vec2(screen_width() / 2.0, screen_height() / 2.0),
+        pos: arena / 2.0,
+        prev_pos: arena / 2.0,
         vel: vec2(0.0, 0.0),
         rotation: 0.0,
-        health: 150.0,
-        max_health: 150.0,
+        prev_rotation: 0.0,
+        health: SHIP_MAX_HEALTH,
+        max_health: SHIP_MAX_HEALTH,
         shoot_timer: 0.0,
         rapid_fire_timer: 0.0,
         engine: Engine::basic(),
+        reverse_engine: Engine::reverse(),
+        max_speed: tuning.max_speed,
         scrap: 0,
         rare_metal: 0,
+        upgrades: Upgrades::default(),
         shield_hp: 0.0,
         shield_max_hp: 0.0,
         shield_timer: 0.0,
@@ -149,7 +280,7 @@ pub fn update_timers(game: &mut Game, dt: f32) {
 
     game.enemy_spawn_timer -= dt;
     if game.enemy_spawn_timer <= 0.0 {
-        game.enemy_ships.push(EnemyShip::new());
+        spawn_enemy(game);
         game.enemy_spawn_timer = game.current_mission.enemy_spawn_interval;
     }
 
@@ -163,61 +294,376 @@ pub fn update_timers(game: &mut Game, dt: f32) {
     });
 }
 
-pub fn update_ship_movement(game: &mut Game, dt: f32) {
-    if is_key_down(KeyCode::Left) {
-        game.ship.rotation -= ROTATION_SPEED * dt;
+// Samples the bound keys, the gamepad and, in the mouse schemes, the mouse into
+// the per-tick input consumed by the simulation. The result is quantized like
+// replays store it (see PlayerInput::quantized).
+// `arena` is the size the renderer fits to the window, to find the cursor in it
+pub fn read_player_input(
+    settings: &Settings,
+    pads: &Gamepads,
+    ship: &Ship,
+    arena: Vec2,
+) -> PlayerInput {
+    let controls = &settings.controls;
+    let pad = pads.state();
+    let mut turn = pad.turn;
+    if controls.is_down(Action::RotateLeft) {
+        turn -= 1.0;
+    }
+    if controls.is_down(Action::RotateRight) {
+        turn += 1.0;
+    }
+    let thrust = if controls.is_down(Action::Thrust) {
+        1.0
+    } else {
+        pad.thrust
+    };
+    let reverse = if controls.is_down(Action::ReverseThrust) {
+        1.0
+    } else {
+        pad.reverse
+    };
+    let mut input = PlayerInput {
+        turn: turn.clamp(-1.0, 1.0),
+        thrust,
+        reverse,
+        brake: controls.is_down(Action::Brake) || pad.brake,
+        fire: controls.is_down(Action::Fire) || pad.fire,
+        pause: controls.is_pressed(Action::Pause) || pads.is_pressed(Action::Pause),
+        aim: None,
+        thrust_heading: None,
+    };
+
+    let scheme = settings.control_scheme;
+    if scheme.uses_mouse() {
+        let cursor = arena_camera(arena).screen_to_world(mouse_position().into());
+        let to_cursor = cursor - ship.pos;
+        // Right on top of the ship the direction is meaningless, so keep the heading.
+        // Holding a rotate key (or the stick) steers by hand instead of the cursor.
+        if to_cursor.length() > MOUSE_AIM_MIN_DISTANCE && input.turn == 0.0 {
+            input.aim = Some(to_cursor.y.atan2(to_cursor.x).to_degrees());
+        }
+        input.fire |= is_mouse_button_down(MouseButton::Left);
+    }
+    match scheme {
+        ControlScheme::Keyboard => {}
+        ControlScheme::MouseAim => {
+            if is_mouse_button_down(MouseButton::Right) {
+                input.thrust = 1.0;
+            }
+        }
+        ControlScheme::MouseAimScreen => {
+            let mut dir = Vec2::ZERO;
+            for (action, step) in [
+                (Action::ThrustScreenUp, vec2(0.0, -1.0)),
+                (Action::ThrustScreenLeft, vec2(-1.0, 0.0)),
+                (Action::ThrustScreenDown, vec2(0.0, 1.0)),
+                (Action::ThrustScreenRight, vec2(1.0, 0.0)),
+            ] {
+                if controls.is_down(action) {
+                    dir += step;
+                }
+            }
+            if dir != Vec2::ZERO {
+                input.thrust = 1.0;
+                input.thrust_heading = Some(dir.y.atan2(dir.x).to_degrees());
+            }
+        }
+    }
+    input.quantized()
+}
+
+// Turns variable frame times into a whole number of fixed simulation ticks
+#[derive(Default)]
+pub struct FixedTimestep {
+    accumulator: f32,
+}
+
+impl FixedTimestep {
+    pub fn new() -> Self {
+        Self { accumulator: 0.0 }
     }
-    if is_key_down(KeyCode::Right) {
-        game.ship.rotation += ROTATION_SPEED * dt;
+
+    // Adds the elapsed frame time and returns how many ticks are due
+    pub fn advance(&mut self, frame_time: f32) -> u32 {
+        self.accumulator += frame_time;
+        let mut ticks = 0;
+        while self.accumulator >= TICK_DT {
+            self.accumulator -= TICK_DT;
+            ticks += 1;
+        }
+        if ticks > MAX_TICKS_PER_FRAME {
+            // A long hitch: skip ahead rather than trying to simulate all of it
+            self.accumulator = 0.0;
+            ticks = MAX_TICKS_PER_FRAME;
+        }
+        ticks
+    }
+
+    // How far rendering is between the last tick and the next one (0.0 - 1.0)
+    pub fn alpha(&self) -> f32 {
+        self.accumulator / TICK_DT
+    }
+
+    // Forget leftover time, e.g. when returning from pause
+    pub fn reset(&mut self) {
+        self.accumulator = 0.0;
     }
+}
 
-    let rotation_rad = game.ship.rotation.to_radians();
-    let ship_dir = vec2(rotation_rad.cos(), rotation_rad.sin());
+// Blends the previous and current tick positions. Wrapped entities jump across the
+// arena, so they are drawn at their new position instead of sliding over the screen.
+pub fn interpolate(prev: Vec2, pos: Vec2, alpha: f32, arena: Vec2) -> Vec2 {
+    let delta = pos - prev;
+    if delta.x.abs() > arena.x / 2.0 || delta.y.abs() > arena.y / 2.0 {
+        return pos;
+    }
+    prev + delta * alpha
+}
+
+fn store_previous_positions(game: &mut Game) {
+    game.ship.prev_pos = game.ship.pos;
+    game.ship.prev_rotation = game.ship.rotation;
+    for b in game.bullets.iter_mut() {
+        b.prev_pos = b.pos;
+    }
+    for a in game.asteroids.iter_mut() {
+        a.prev_pos = a.pos;
+    }
+    for e in game.enemy_ships.iter_mut() {
+        e.prev_pos = e.pos;
+    }
+    for item in game.loot_items.iter_mut() {
+        item.prev_pos = item.pos;
+    }
+}
+
+// Runs one step of the simulation pipeline. Returns true if the game is over.
+// Touches neither the window nor the keyboard, so it can be driven headlessly.
+// Callers are expected to use a fixed dt (see TICK_DT) to keep runs reproducible.
+pub fn update_game(game: &mut Game, input: &PlayerInput, dt: f32) -> bool {
+    game.events.clear();
+    store_previous_positions(game);
+    update_timers(game, dt);
+    update_ship_movement(game, input, dt);
+    update_ship_shooting(game, input);
+    update_enemies(game, dt);
+    update_loot(game, dt);
+    update_physics(game, dt);
+    let game_over = update_collisions(game);
+    dispatch_events(game);
+    game_over
+}
+
+// Subscribers every game starts with, in the order they are called
+const DEFAULT_SUBSCRIBERS: [fn(&mut Game, &GameEvent); 5] = [
+    apply_loot,
+    award_score,
+    track_mission_progress,
+    spawn_explosions,
+    drop_loot,
+];
+
+// Hands every event emitted this tick to the subscribers. Events emitted by a
+// subscriber (e.g. LootSpawned) are delivered in the same pass.
+pub fn dispatch_events(game: &mut Game) {
+    // Taken out of the game while they run, so each can change the game
+    let mut subscribers = std::mem::take(&mut game.subscribers);
+    let mut i = 0;
+    while i < game.events.len() {
+        let event = game.events[i].clone();
+        for subscriber in &mut subscribers {
+            subscriber.on_event(game, &event);
+        }
+        // Registered during this event; they start with the next one
+        subscribers.append(&mut game.subscribers);
+        i += 1;
+    }
+    game.subscribers = subscribers;
+}
+
+// Gives the ship what it picked up
+fn apply_loot(game: &mut Game, event: &GameEvent) {
+    let GameEvent::LootCollected { item } = event else {
+        return;
+    };
+    match *item {
+        LootType::Scrap(amount) => game.ship.scrap += amount,
+        LootType::RareMetal(amount) => game.ship.rare_metal += amount,
+        LootType::HealthPack(hp) => game.ship.heal(hp as f32),
+        LootType::RapidFireBoost => game.ship.rapid_fire_timer = 10.0,
+        LootType::BigBulletBoost => game.ship.big_bullet_timer = 15.0,
+        LootType::Shield(hp) => {
+            let shield_factor = 1.0 + game.upgrade_bonus(Upgrade::ShieldCapacity);
+            game.ship.activate_shield(hp as f32 * shield_factor, 30.0);
+        }
+    }
+}
 
-    let is_gas_pedal_down = is_key_down(KeyCode::Up);
-    game.ship.engine.update(dt, is_gas_pedal_down);
+fn award_score(game: &mut Game, event: &GameEvent) {
+    match event {
+        GameEvent::EnemyDestroyed { max_health, .. } => {
+            game.score += (*max_health as u32) * SCORE_PER_ENEMY_HP;
+        }
+        GameEvent::AsteroidSplit { .. } => game.score += 100,
+        _ => {}
+    }
+}
+
+fn track_mission_progress(game: &mut Game, event: &GameEvent) {
+    match event {
+        GameEvent::EnemyDestroyed { .. } => game.mission_kills += 1,
+        GameEvent::LootCollected {
+            item: LootType::Scrap(amount),
+        } => game.mission_scrap_collected += amount,
+        GameEvent::LootCollected {
+            item: LootType::RareMetal(amount),
+        } => game.mission_rare_metal_collected += amount,
+        _ => {}
+    }
+}
+
+fn spawn_explosions(game: &mut Game, event: &GameEvent) {
+    let (pos, scale) = match event {
+        GameEvent::BulletIntercepted { pos } => (*pos, 0.5),
+        GameEvent::EnemyDestroyed { pos, rammed, .. } => (*pos, if *rammed { 0.6 } else { 0.4 }),
+        GameEvent::PlayerDamaged { pos, source, .. } => match source {
+            DamageSource::EnemyBullet => (*pos, 0.5),
+            DamageSource::Asteroid { radius } => (*pos, (radius / 40.0).clamp(0.3, 0.8)),
+            DamageSource::Kamikaze => return, // The kamikaze's own explosion covers it
+        },
+        _ => return,
+    };
+    game.explosions.push(Explosion::new(pos, scale));
+}
+
+fn drop_loot(game: &mut Game, event: &GameEvent) {
+    let (pos, source) = match event {
+        GameEvent::EnemyDestroyed { pos, loot, .. } => (*pos, *loot),
+        GameEvent::AsteroidSplit { pos, is_rare, .. } => {
+            let source = if *is_rare {
+                LootSource::RareAsteroid
+            } else {
+                LootSource::Asteroid
+            };
+            (*pos, source)
+        }
+        _ => return,
+    };
+    if let Some(loot) = generate_loot(pos, source, game.difficulty, &game.data.loot, &game.rng) {
+        game.emit(GameEvent::LootSpawned {
+            pos,
+            item: loot.item_type.clone(),
+        });
+        game.loot_items.push(loot);
+    }
+}
+
+// Applies damage to the player and reports it. Returns true if the ship is destroyed.
+fn damage_player(game: &mut Game, amount: f32, source: DamageSource) -> bool {
+    let had_shield = game.ship.shield_hp > 0.0;
+    let destroyed = game.ship.take_damage(amount);
+    game.emit(GameEvent::PlayerDamaged {
+        pos: game.ship.pos,
+        amount,
+        source,
+    });
+    if had_shield && game.ship.shield_hp <= 0.0 {
+        game.emit(GameEvent::ShieldBroken);
+    }
+    destroyed
+}
+
+pub fn update_ship_movement(game: &mut Game, input: &PlayerInput, dt: f32) {
+    let tuning = &game.data.tuning;
+    let max_turn = tuning.rotation_speed * dt;
+    match input.aim {
+        // Turns towards the aim no faster than the keys would
+        Some(aim) => {
+            game.ship.rotation += angle_diff(game.ship.rotation, aim).clamp(-max_turn, max_turn)
+        }
+        None => game.ship.rotation += input.turn.clamp(-1.0, 1.0) * max_turn,
+    }
+
+    let heading = input
+        .thrust_heading
+        .unwrap_or(game.ship.rotation)
+        .to_radians();
+    let thrust_dir = vec2(heading.cos(), heading.sin());
+
+    game.ship.engine.update(dt, input.thrust);
     if game.ship.engine.current_thrust > 0.0 {
-        let thrust_force = game.ship.engine.current_thrust * ACCELERATION;
-        game.ship.vel += ship_dir * thrust_force * dt;
+        let thrust_force = game.ship.engine.current_thrust * tuning.acceleration;
+        game.ship.vel += thrust_dir * thrust_force * dt;
+    }
+
+    // The reverse thrusters always push against the nose
+    game.ship.reverse_engine.update(dt, input.reverse);
+    if game.ship.reverse_engine.current_thrust > 0.0 {
+        let rotation_rad = game.ship.rotation.to_radians();
+        let ship_dir = vec2(rotation_rad.cos(), rotation_rad.sin());
+        let thrust_force = game.ship.reverse_engine.current_thrust * tuning.reverse_acceleration;
+        game.ship.vel -= ship_dir * thrust_force * dt;
+    }
+
+    if input.brake {
+        let speed = game.ship.vel.length();
+        let slowed = (speed - tuning.brake_deceleration * dt).max(0.0);
+        game.ship.vel = game.ship.vel.normalize_or_zero() * slowed;
     }
+    game.ship.vel = game.ship.vel.clamp_length_max(game.ship.max_speed);
 
     game.ship.pos += game.ship.vel * dt;
-    wrap_around(&mut game.ship.pos);
+    wrap_around(&mut game.ship.pos, game.arena);
 }
 
-pub fn update_ship_shooting(game: &mut Game) {
+pub fn update_ship_shooting(game: &mut Game, input: &PlayerInput) {
+    let cooldown_factor = 1.0 - game.upgrade_bonus(Upgrade::FireRate);
+    let damage_factor = 1.0 + game.upgrade_bonus(Upgrade::BulletDamage);
+    let tuning = &game.data.tuning;
     let current_cooldown = if game.ship.rapid_fire_timer > 0.0 {
-        SHOOT_COOLDOWN / 3.0
+        tuning.shoot_cooldown * cooldown_factor / 3.0
     } else {
-        SHOOT_COOLDOWN
+        tuning.shoot_cooldown * cooldown_factor
     };
 
-    if is_key_down(KeyCode::Space) && game.ship.shoot_timer <= 0.0 {
+    if input.fire && game.ship.shoot_timer <= 0.0 {
         let rotation_rad = game.ship.rotation.to_radians();
         let ship_dir = vec2(rotation_rad.cos(), rotation_rad.sin());
 
         let (damage, radius) = if game.ship.big_bullet_timer > 0.0 {
-            (BIG_BULLET_DAMAGE, BIG_BULLET_RADIUS)
+            (tuning.big_bullet_damage, tuning.big_bullet_radius)
         } else {
-            (PLAYER_BULLET_DAMAGE, PLAYER_BULLET_RADIUS)
+            (tuning.player_bullet_damage, tuning.player_bullet_radius)
         };
 
         game.bullets.push(Bullet {
             pos: game.ship.pos,
-            vel: ship_dir * BULLET_SPEED + game.ship.vel,
-            life_time: BULLET_LIFETIME,
+            prev_pos: game.ship.pos,
+            vel: ship_dir * tuning.bullet_speed + game.ship.vel,
+            life_time: tuning.bullet_lifetime,
             style: BulletStyle::Player,
-            damage,
+            damage: damage * damage_factor,
             radius,
         });
         game.ship.shoot_timer = current_cooldown;
     }
 }
 
+// Spawns an enemy picked from the current mission's archetype mix
+pub fn spawn_enemy(game: &mut Game) {
+    let enemies = &game.data.enemies;
+    let Some(idx) = enemies.pick(&game.current_mission.enemy_mix, &game.rng) else {
+        return;
+    };
+    let enemy = EnemyShip::new(game.arena, idx, &enemies.archetypes[idx], &game.rng);
+    game.enemy_ships.push(enemy);
+}
+
 pub fn update_enemies(game: &mut Game, dt: f32) {
     game.enemy_spawn_timer -= dt;
     if game.enemy_spawn_timer <= 0.0 {
-        game.enemy_ships.push(EnemyShip::new());
+        spawn_enemy(game);
 
         // Apply difficulty multiplier to spawn rate
         let base_interval = game.current_mission.enemy_spawn_interval;
@@ -232,43 +678,49 @@ pub fn update_enemies(game: &mut Game, dt: f32) {
             EnemyType::Regular => {
                 // Regular enemy: move horizontally and shoot
                 e.pos += e.vel * dt;
+                let Some(weapon) = e.weapon else {
+                    continue;
+                };
                 e.shoot_timer -= dt;
 
                 if e.shoot_timer <= 0.0 {
-                    let bullet_vel = vec2(e.rotation.cos(), e.rotation.sin()) * 250.0;
+                    let bullet_vel = vec2(e.rotation.cos(), e.rotation.sin()) * weapon.bullet_speed;
 
                     game.bullets.push(Bullet {
                         pos: e.pos,
+                        prev_pos: e.pos,
                         vel: bullet_vel,
-                        life_time: 4.0,
+                        life_time: weapon.bullet_lifetime,
                         style: BulletStyle::Enemy,
-                        damage: ENEMY_BULLET_DAMAGE,
-                        radius: 9.0,
+                        damage: weapon.damage,
+                        radius: weapon.bullet_radius,
                     });
-                    e.shoot_timer = 2.0;
+                    e.shoot_timer = weapon.interval;
                 }
             }
             EnemyType::Kamikaze => {
                 // Kamikaze enemy: fly directly toward player
                 let dir = diff.normalize();
-                let kamikaze_speed = 180.0; // Speed toward player
-                e.vel = dir * kamikaze_speed;
+                e.vel = dir * e.speed;
                 e.pos += e.vel * dt;
             }
         }
     }
+    let arena_w = game.arena.x;
     game.enemy_ships
-        .retain(|e| e.pos.x > -100.0 && e.pos.x < screen_width() + 100.0);
+        .retain(|e| e.pos.x > -100.0 && e.pos.x < arena_w + 100.0);
 }
 
 pub fn update_loot(game: &mut Game, dt: f32) {
-    let mut items_to_remove = Vec::new();
+    let mut collected = Vec::new();
+    let magnet_radius = game.data.tuning.magnet_radius + game.upgrade_bonus(Upgrade::MagnetRadius);
+    let tuning = &game.data.tuning;
 
-    for (i, item) in game.loot_items.iter_mut().enumerate() {
-        item.vel *= 0.95;
+    game.loot_items.retain_mut(|item| {
+        item.vel *= LOOT_VELOCITY_RETENTION.powf(dt * 60.0);
         item.pos += item.vel * dt;
         item.pos += item.drift_vel * dt;
-        wrap_around(&mut item.pos);
+        wrap_around(&mut item.pos, game.arena);
 
         item.rotation += item.rotation_speed * dt;
         if item.rotation > std::f32::consts::PI * 2.0 {
@@ -279,45 +731,24 @@ pub fn update_loot(game: &mut Game, dt: f32) {
 
         let dist_to_ship = (game.ship.pos - item.pos).length();
 
-        if dist_to_ship < 150.0 {
+        if dist_to_ship < magnet_radius {
             item.magnet_active = true;
         }
 
         if item.magnet_active {
             let dir = (game.ship.pos - item.pos).normalize();
-            let magnet_speed = 300.0;
-            item.pos += dir * magnet_speed * dt;
+            item.pos += dir * tuning.magnet_speed * dt;
         }
 
         if dist_to_ship < (72.0 / 2.0 + item.radius) {
-            match item.item_type {
-                LootType::Scrap(amount) => {
-                    game.ship.scrap += amount;
-                    game.mission_scrap_collected += amount;
-                }
-                LootType::RareMetal(amount) => {
-                    game.ship.rare_metal += amount;
-                    game.mission_rare_metal_collected += amount;
-                }
-                LootType::HealthPack(hp) => {
-                    game.ship.heal(hp as f32);
-                }
-                LootType::RapidFireBoost => {
-                    game.ship.rapid_fire_timer = 10.0;
-                }
-                LootType::BigBulletBoost => {
-                    game.ship.big_bullet_timer = 15.0;
-                }
-                LootType::Shield(hp) => {
-                    game.ship.activate_shield(hp as f32, 30.0);
-                }
-            }
-            items_to_remove.push(i);
+            collected.push(item.item_type.clone());
+            return false;
         }
-    }
+        true
+    });
 
-    for &i in items_to_remove.iter().rev() {
-        game.loot_items.remove(i);
+    for item in collected {
+        game.emit(GameEvent::LootCollected { item });
     }
 }
 
@@ -330,187 +761,203 @@ pub fn update_physics(game: &mut Game, dt: f32) {
 
     for a in game.asteroids.iter_mut() {
         a.pos += a.vel * dt;
-        wrap_around(&mut a.pos);
+        wrap_around(&mut a.pos, game.arena);
     }
 }
 
 pub fn update_collisions(game: &mut Game) -> bool {
     let mut new_asteroids = Vec::new();
+    let mut events = Vec::new();
     let mut game_over = false;
+    let mut candidates = Vec::new();
+
+    // Entities destroyed this tick; they are dropped in one pass at the end
+    // instead of being removed one by one while the grids still index them
+    let mut bullet_alive = vec![true; game.bullets.len()];
+    let mut asteroid_alive = vec![true; game.asteroids.len()];
+    let mut enemy_alive = vec![true; game.enemy_ships.len()];
 
     // Player bullets vs Enemy bullets (bullets explode each other)
     // Check this FIRST before other collisions
-    let mut bullets_to_remove = HashSet::new();
-    for (i, player_bullet) in game.bullets.iter().enumerate() {
+    let mut enemy_bullet_grid = SpatialGrid::new(game.arena, COLLISION_CELL_SIZE);
+    for (j, b) in game.bullets.iter().enumerate() {
+        if b.style == BulletStyle::Enemy {
+            enemy_bullet_grid.insert(j, b.pos, b.radius);
+        }
+    }
+    for i in 0..game.bullets.len() {
+        let player_bullet = &game.bullets[i];
         if player_bullet.style != BulletStyle::Player {
             continue;
         }
-        if bullets_to_remove.contains(&i) {
-            continue; // Already marked for removal
-        }
-        for (j, enemy_bullet) in game.bullets.iter().enumerate().skip(i + 1) {
-            if enemy_bullet.style != BulletStyle::Enemy {
-                continue;
-            }
-            if bullets_to_remove.contains(&j) {
-                continue; // Already marked for removal
+        enemy_bullet_grid.query(player_bullet.pos, player_bullet.radius, &mut candidates);
+        for &j in &candidates {
+            if !bullet_alive[j] {
+                continue; // Already intercepted
             }
+            let enemy_bullet = &game.bullets[j];
             let distance = (player_bullet.pos - enemy_bullet.pos).length();
             if distance < player_bullet.radius + enemy_bullet.radius {
-                // Bullets collide - create explosion at midpoint
+                // Bullets collide - explode at midpoint
                 let collision_pos = (player_bullet.pos + enemy_bullet.pos) * 0.5;
-                game.explosions.push(Explosion::new(collision_pos, 0.5));
-                bullets_to_remove.insert(i);
-                bullets_to_remove.insert(j);
+                events.push(GameEvent::BulletIntercepted { pos: collision_pos });
+                bullet_alive[i] = false;
+                bullet_alive[j] = false;
                 break; // This player bullet is destroyed, move to next
             }
         }
     }
-    // Remove collided bullets (sort in reverse to maintain indices)
-    let mut sorted_indices: Vec<usize> = bullets_to_remove.into_iter().collect();
-    sorted_indices.sort_unstable_by(|a, b| b.cmp(a));
-    for &idx in &sorted_indices {
-        if idx < game.bullets.len() {
-            game.bullets.remove(idx);
-        }
-    }
 
     // Player bullets vs asteroids and enemies
-    game.bullets.retain(|b| {
-        if b.style != BulletStyle::Player {
-            return true;
+    let mut asteroid_grid = SpatialGrid::new(game.arena, COLLISION_CELL_SIZE);
+    for (i, a) in game.asteroids.iter().enumerate() {
+        asteroid_grid.insert(i, a.pos, a.radius);
+    }
+    let mut enemy_grid = SpatialGrid::new(game.arena, COLLISION_CELL_SIZE);
+    for (i, e) in game.enemy_ships.iter().enumerate() {
+        enemy_grid.insert(i, e.pos, e.radius);
+    }
+
+    for (bullet_idx, b) in game.bullets.iter().enumerate() {
+        if b.style != BulletStyle::Player || !bullet_alive[bullet_idx] {
+            continue;
         }
 
         let mut hit = false;
 
-        // Check asteroid collisions
-        for i in (0..game.asteroids.len()).rev() {
-            if (b.pos - game.asteroids[i].pos).length() < game.asteroids[i].radius + b.radius {
-                game.score += 100;
-                let is_rare = game.asteroids[i].is_rare;
-                let asteroid_pos = game.asteroids[i].pos;
-
-                if is_rare {
-                    if let Some(loot) = generate_loot(
-                        asteroid_pos,
-                        crate::systems::LootSource::RareAsteroid,
-                        game.difficulty,
-                    ) {
-                        game.loot_items.push(loot);
-                    }
-                } else if let Some(loot) = generate_loot(
-                    asteroid_pos,
-                    crate::systems::LootSource::Asteroid,
-                    game.difficulty,
-                ) {
-                    game.loot_items.push(loot);
-                }
+        // Check asteroid collisions (latest spawned asteroid wins, as before)
+        asteroid_grid.query(b.pos, b.radius, &mut candidates);
+        for &i in candidates.iter().rev() {
+            let asteroid = &game.asteroids[i];
+            if !asteroid_alive[i] || (b.pos - asteroid.pos).length() >= asteroid.radius + b.radius {
+                continue;
+            }
 
-                let old = game.asteroids.remove(i);
-                if old.radius > 15.0 {
-                    new_asteroids.push(Asteroid::new_fragment(old.pos, old.radius));
-                    new_asteroids.push(Asteroid::new_fragment(old.pos, old.radius));
-                }
-                hit = true;
-                break;
+            asteroid_alive[i] = false;
+            let splits = asteroid.radius > 15.0;
+            events.push(GameEvent::AsteroidSplit {
+                pos: asteroid.pos,
+                radius: asteroid.radius,
+                is_rare: asteroid.is_rare,
+                fragments: if splits { 2 } else { 0 },
+            });
+            if splits {
+                new_asteroids.push(Asteroid::new_fragment(
+                    asteroid.pos,
+                    asteroid.radius,
+                    &game.rng,
+                ));
+                new_asteroids.push(Asteroid::new_fragment(
+                    asteroid.pos,
+                    asteroid.radius,
+                    &game.rng,
+                ));
             }
+            hit = true;
+            break;
         }
 
-        // Check enemy collisions
-        game.enemy_ships.retain_mut(|e| {
-            if (b.pos - e.pos).length() < 30.0 + b.radius {
-                hit = true;
-                if e.take_damage(b.damage) {
-                    let score_gain = (e.max_health as u32) * SCORE_PER_ENEMY_HP;
-                    game.score += score_gain;
-                    if let Some(loot) = generate_loot(
-                        e.pos,
-                        crate::systems::LootSource::EnemySmall,
-                        game.difficulty,
-                    ) {
-                        game.loot_items.push(loot);
-                    }
-                    game.mission_kills += 1;
-                    game.explosions.push(Explosion::new(e.pos, 0.4));
-                    false
-                } else {
-                    true
-                }
-            } else {
-                true
+        // Check enemy collisions (a bullet damages every enemy it overlaps)
+        enemy_grid.query(b.pos, b.radius, &mut candidates);
+        for &i in &candidates {
+            let e = &mut game.enemy_ships[i];
+            if !enemy_alive[i] || (b.pos - e.pos).length() >= e.radius + b.radius {
+                continue;
             }
-        });
+            hit = true;
+            if e.take_damage(b.damage) {
+                events.push(GameEvent::EnemyDestroyed {
+                    pos: e.pos,
+                    max_health: e.max_health,
+                    loot: e.loot,
+                    rammed: false,
+                });
+                enemy_alive[i] = false;
+            }
+        }
 
-        !hit
-    });
-    game.asteroids.extend(new_asteroids);
+        if hit {
+            bullet_alive[bullet_idx] = false;
+        }
+    }
+
+    game.events.append(&mut events);
 
     // Enemy bullets vs player
-    game.bullets.retain(|b| {
-        if b.style == BulletStyle::Enemy && (b.pos - game.ship.pos).length() < 20.0 + b.radius {
-            game.explosions.push(Explosion::new(game.ship.pos, 0.5));
+    for (i, alive) in bullet_alive.iter_mut().enumerate() {
+        let b = &game.bullets[i];
+        if !*alive || b.style != BulletStyle::Enemy {
+            continue;
+        }
+        if (b.pos - game.ship.pos).length() < 20.0 + b.radius {
             let damage = b.damage * game.difficulty.damage_mult();
-            if game.ship.take_damage(damage, game.score) {
+            if damage_player(game, damage, DamageSource::EnemyBullet) {
                 game_over = true;
             }
-            false
-        } else {
-            true
+            *alive = false;
         }
-    });
+    }
 
     // Ship vs asteroids
-    for i in (0..game.asteroids.len()).rev() {
-        if (game.ship.pos - game.asteroids[i].pos).length() < game.asteroids[i].radius + 10.0 {
-            let base_asteroid_damage = (game.asteroids[i].radius / 10.0) * BASE_ASTEROID_DAMAGE;
-            let asteroid_damage = base_asteroid_damage * game.difficulty.damage_mult();
-            let asteroid_radius = game.asteroids[i].radius;
-            game.asteroids.remove(i);
-            let explosion_scale = (asteroid_radius / 40.0).clamp(0.3, 0.8);
-            game.explosions
-                .push(Explosion::new(game.ship.pos, explosion_scale));
-            if game.ship.take_damage(asteroid_damage, game.score) {
-                game_over = true;
-            }
+    asteroid_grid.query(game.ship.pos, 10.0, &mut candidates);
+    for &i in candidates.iter().rev() {
+        let asteroid = &game.asteroids[i];
+        if !asteroid_alive[i] || (game.ship.pos - asteroid.pos).length() >= asteroid.radius + 10.0 {
+            continue;
+        }
+        let radius = asteroid.radius;
+        let base_asteroid_damage = (radius / 10.0) * game.data.tuning.asteroid_damage;
+        let asteroid_damage = base_asteroid_damage * game.difficulty.damage_mult();
+        asteroid_alive[i] = false;
+        if damage_player(game, asteroid_damage, DamageSource::Asteroid { radius }) {
+            game_over = true;
         }
     }
 
     // Ship vs kamikaze enemies (explode on contact)
-    game.enemy_ships.retain_mut(|e| {
-        if e.enemy_type == EnemyType::Kamikaze {
-            let distance = (game.ship.pos - e.pos).length();
-            let enemy_radius = 22.5; // Smaller radius for kamikaze (45.0 size / 2)
-            let ship_radius = 10.0; // Ship radius approximation
-            if distance < enemy_radius + ship_radius {
-                // Kamikaze explodes on contact
-                let kamikaze_damage = BASE_KAMIKAZE_DAMAGE * game.difficulty.damage_mult();
-                game.explosions.push(Explosion::new(e.pos, 0.6));
-                if game.ship.take_damage(kamikaze_damage, game.score) {
-                    game_over = true;
-                }
-                // Award score for destroying kamikaze
-                let score_gain = (e.max_health as u32) * SCORE_PER_ENEMY_HP;
-                game.score += score_gain;
-                if let Some(loot) = generate_loot(
-                    e.pos,
-                    crate::systems::LootSource::EnemySmall,
-                    game.difficulty,
-                ) {
-                    game.loot_items.push(loot);
-                }
-                game.mission_kills += 1;
-                return false; // Remove the kamikaze enemy
+    for (i, alive) in enemy_alive.iter_mut().enumerate() {
+        let e = &game.enemy_ships[i];
+        if !*alive || e.enemy_type != EnemyType::Kamikaze {
+            continue;
+        }
+        let distance = (game.ship.pos - e.pos).length();
+        let ship_radius = 10.0; // Ship radius approximation
+        if distance < e.radius + ship_radius {
+            // Kamikaze explodes on contact, and counts as destroyed
+            let destroyed = GameEvent::EnemyDestroyed {
+                pos: e.pos,
+                max_health: e.max_health,
+                loot: e.loot,
+                rammed: true,
+            };
+            let ram_damage = e.ram_damage * game.difficulty.damage_mult();
+            if damage_player(game, ram_damage, DamageSource::Kamikaze) {
+                game_over = true;
             }
+            game.emit(destroyed);
+            *alive = false; // Remove the kamikaze enemy
         }
-        true // Keep the enemy
-    });
+    }
+
+    // Sweep out everything destroyed this tick
+    let mut alive = bullet_alive.into_iter();
+    game.bullets.retain(|_| alive.next().unwrap_or(true));
+    let mut alive = asteroid_alive.into_iter();
+    game.asteroids.retain(|_| alive.next().unwrap_or(true));
+    let mut alive = enemy_alive.into_iter();
+    game.enemy_ships.retain(|_| alive.next().unwrap_or(true));
+    game.asteroids.extend(new_asteroids);
 
     game_over
 }
 
-pub fn render_game(game: &Game, resources: &Resources) {
+// `alpha` is the progress towards the next simulation tick (see FixedTimestep::alpha)
+pub fn render_game(game: &Game, resources: &Resources, alpha: f32) {
+    let lerp = |prev: Vec2, pos: Vec2| interpolate(prev, pos, alpha, game.arena);
+    set_camera(&arena_camera(game.arena));
+
     for item in &game.loot_items {
-        draw_loot(item, resources);
+        draw_loot(item, lerp(item.prev_pos, item.pos), resources);
     }
 
     for b in &game.bullets {
@@ -521,11 +968,12 @@ pub fn render_game(game: &Game, resources: &Resources) {
 
         let rotation = b.vel.y.atan2(b.vel.x) + std::f32::consts::FRAC_PI_2;
         let size = b.radius * 2.0;
+        let pos = lerp(b.prev_pos, b.pos);
 
         draw_texture_ex(
             texture,
-            b.pos.x - size / 2.0,
-            b.pos.y - size / 2.0,
+            pos.x - size / 2.0,
+            pos.y - size / 2.0,
             WHITE,
             DrawTextureParams {
                 dest_size: Some(vec2(size, size)),
@@ -536,53 +984,66 @@ pub fn render_game(game: &Game, resources: &Resources) {
     }
 
     for a in &game.asteroids {
-        draw_asteroid(a, resources);
+        draw_asteroid(a, lerp(a.prev_pos, a.pos), resources);
     }
 
     for e in &game.enemy_ships {
-        draw_enemy(e, resources);
+        draw_enemy(e, lerp(e.prev_pos, e.pos), resources);
     }
 
     for ex in &game.explosions {
         draw_explosion(ex, resources);
     }
 
+    let ship_rotation =
+        game.ship.prev_rotation + (game.ship.rotation - game.ship.prev_rotation) * alpha;
     draw_ship(
         &game.ship,
+        lerp(game.ship.prev_pos, game.ship.pos),
+        ship_rotation,
         &resources.ship_body,
         &resources.ship_flame,
         Some(&resources.shield_active),
     );
 
+    // The HUD stays in window pixels
+    set_default_camera();
+
+    let lang = &resources.lang;
     let mut status_text = format!(
-        "{} {}  {} {:.0}/{:.0}",
-        resources.lang.t("score"),
-        game.score,
-        resources.lang.t("hp"),
-        game.ship.health,
-        game.ship.max_health
+        "{}  {}",
+        lang.format("hud_score", &[("score", game.score.into())]),
+        lang.format(
+            "hud_hp",
+            &[
+                ("hp", game.ship.health.into()),
+                ("max", game.ship.max_health.into()),
+            ],
+        )
     );
     if game.ship.has_shield() {
-        status_text.push_str(&format!(
-            "  {} {:.0}/{:.0}",
-            resources.lang.t("shield"),
-            game.ship.shield_hp,
-            game.ship.shield_max_hp
+        status_text.push_str("  ");
+        status_text.push_str(&lang.format(
+            "hud_shield",
+            &[
+                ("hp", game.ship.shield_hp.into()),
+                ("max", game.ship.shield_max_hp.into()),
+            ],
         ));
     }
     crate::draw::draw_text_with_font(&status_text, 20.0, 30.0, 24.0, WHITE, resources);
 
-    let status = format!(
-        "{} {}/{}  {} {}/{}  {} {}/{}",
-        resources.lang.t("defeated"),
-        game.mission_kills,
-        game.current_mission.target_kills,
-        resources.lang.t("rust"),
-        game.mission_scrap_collected,
-        game.current_mission.target_scrap,
-        resources.lang.t("gold"),
-        game.mission_rare_metal_collected,
-        game.current_mission.target_rare_metal
+    let mission = &game.current_mission;
+    let status = lang.format(
+        "hud_progress",
+        &[
+            ("kills", game.mission_kills.into()),
+            ("target_kills", mission.target_kills.into()),
+            ("scrap", game.mission_scrap_collected.into()),
+            ("target_scrap", mission.target_scrap.into()),
+            ("gold", game.mission_rare_metal_collected.into()),
+            ("target_gold", mission.target_rare_metal.into()),
+        ],
     );
     crate::draw::draw_text_with_font(
         &status,
@@ -593,13 +1054,12 @@ pub fn render_game(game: &Game, resources: &Resources) {
         resources,
     );
 
-    let inventory = format!(
-        "{} {} {} | {} {}",
-        resources.lang.t("resources"),
-        resources.lang.t("rust"),
-        game.ship.scrap,
-        resources.lang.t("gold"),
-        game.ship.rare_metal
+    let inventory = lang.format(
+        "hud_inventory",
+        &[
+            ("scrap", game.ship.scrap.into()),
+            ("gold", game.ship.rare_metal.into()),
+        ],
     );
     crate::draw::draw_text_with_font(
         &inventory,
@@ -611,7 +1071,7 @@ pub fn render_game(game: &Game, resources: &Resources) {
     );
 }
 
-pub fn render_menu(game: &Game, res: &Resources) {
+pub fn render_menu(game: &Game, can_continue: bool, settings: &Settings, res: &Resources) {
     draw_background(&res.background);
 
     // 1. Logo rendering - smaller and at top
@@ -639,184 +1099,535 @@ pub fn render_menu(game: &Game, res: &Resources) {
         },
     );
 
-    // 2. Menu items - centered, with selection highlighting
-    let base_y = logo_h / 2.0 - 50.0;
-    let item_spacing = 60.0;
-
-    // Font sizes: Start is bigger, others smaller
-    let start_font_size = 32;
-    let start_selected_font_size = 36;
-    let other_font_size = 16;
-    let other_selected_font_size = 18;
-
-    // Start menu item - biggest font
-    let start_y = base_y + 40.0;
-    let is_selected = game.menu_selection == MenuItem::Start;
-    let start_color = if is_selected { YELLOW } else { WHITE };
-    let start_size = if is_selected {
-        start_selected_font_size
-    } else {
-        start_font_size
-    };
-    let start_prefix = if is_selected { "> " } else { "  " };
-    draw_text_centered(
-        &format!("{}{}", start_prefix, res.lang.t("menu_start")),
-        start_y,
-        start_size,
-        start_color,
-        res,
-    );
-
-    // Difficulty menu item - smaller font, no label
-    let diff_y = start_y + item_spacing;
-    let is_selected = game.menu_selection == MenuItem::Difficulty;
-    let diff_color = if is_selected { YELLOW } else { WHITE };
-    let diff_size = if is_selected {
-        other_selected_font_size
-    } else {
-        other_font_size
-    };
-    let diff_prefix = if is_selected { "> " } else { "  " };
-
-    let diff_key = match game.difficulty {
-        Difficulty::Nebula => "diff_nebula",
-        Difficulty::Supernova => "diff_supernova",
-        Difficulty::BlackHole => "diff_blackhole",
-    };
-    let diff_text = res.lang.t(diff_key);
-
-    // Use difficulty color when selected, otherwise white
-    let final_diff_color = if is_selected {
-        match game.difficulty {
-            Difficulty::Nebula => GREEN,
-            Difficulty::Supernova => YELLOW,
-            Difficulty::BlackHole => RED,
-        }
-    } else {
-        diff_color
-    };
-
-    draw_text_centered(
-        &format!("{diff_prefix}< {diff_text} >"),
-        diff_y,
-        diff_size,
-        final_diff_color,
-        res,
-    );
-
-    // Language menu item - smaller font, no label
-    let lang_y = diff_y + item_spacing;
-    let is_selected = game.menu_selection == MenuItem::Language;
-    let lang_color = if is_selected { YELLOW } else { WHITE };
-    let lang_size = if is_selected {
-        other_selected_font_size
-    } else {
-        other_font_size
-    };
-    let lang_prefix = if is_selected { "> " } else { "  " };
-
+    // 2. Menu items - centered, with selection highlighting.
+    // Continue and Start use a big font, the options a smaller one.
     let lang_text = match res.lang.current_lang {
         crate::localization::Language::English => res.lang.t("lang_english"),
         crate::localization::Language::Russian => res.lang.t("lang_russian"),
         crate::localization::Language::German => res.lang.t("lang_german"),
     };
+    let option = |key: &str| format!("< {} >", res.lang.t(key));
+
+    // (item, text, color when selected)
+    let mut items = Vec::new();
+    if can_continue {
+        items.push((
+            MenuItem::Continue,
+            res.lang.t("menu_continue").to_string(),
+            YELLOW,
+        ));
+    }
+    items.extend([
+        (
+            MenuItem::Start,
+            res.lang.t("menu_start").to_string(),
+            YELLOW,
+        ),
+        (
+            MenuItem::HighScores,
+            res.lang.t("menu_high_scores").to_string(),
+            YELLOW,
+        ),
+        (
+            MenuItem::Difficulty,
+            option(game.difficulty.name_key()),
+            game.difficulty.color(),
+        ),
+        (MenuItem::Language, format!("< {lang_text} >"), YELLOW),
+        (
+            MenuItem::WindowMode,
+            option(settings.window_mode.name_key()),
+            YELLOW,
+        ),
+        (
+            MenuItem::ControlScheme,
+            option(settings.control_scheme.name_key()),
+            YELLOW,
+        ),
+        (
+            MenuItem::Controls,
+            res.lang.t("menu_controls").to_string(),
+            YELLOW,
+        ),
+    ]);
 
-    draw_text_centered(
-        &format!("{lang_prefix}< {lang_text} >"),
-        lang_y,
-        lang_size,
-        lang_color,
-        res,
-    );
+    // Start stays in place whether or not Continue is shown above it
+    let start_y = logo_h / 2.0 - 10.0;
+    let item_spacing = 52.0; // Tight enough for every option to fit under the logo
+    let first_y = if can_continue {
+        start_y - item_spacing
+    } else {
+        start_y
+    };
+
+    for (i, (item, text, selected_color)) in items.iter().enumerate() {
+        let is_selected = game.menu_selection == *item;
+        let big = matches!(item, MenuItem::Continue | MenuItem::Start);
+        let size = match (big, is_selected) {
+            (true, true) => 36,
+            (true, false) => 32,
+            (false, true) => 18,
+            (false, false) => 16,
+        };
+        let color = if is_selected { *selected_color } else { WHITE };
+        let prefix = if is_selected { "> " } else { "  " };
+        draw_text_centered(
+            &format!("{prefix}{text}"),
+            first_y + i as f32 * item_spacing,
+            size,
+            color,
+            res,
+        );
+    }
 
     // Instructions at bottom
+    let hint = key_hint(
+        "menu_instructions",
+        &[
+            ("up", Action::NavigateUp),
+            ("down", Action::NavigateDown),
+            ("left", Action::NavigateLeft),
+            ("right", Action::NavigateRight),
+            ("confirm", Action::Confirm),
+        ],
+        &settings.controls,
+        res,
+    );
     draw_text_centered(
-        res.lang.t("menu_instructions"),
-        base_y + 250.0,
+        &hint,
+        first_y + items.len() as f32 * item_spacing + 30.0,
         14,
         GRAY,
         res,
     );
 }
 
-pub fn render_briefing(mission: &Mission, res: &Resources) {
+// Fills `{name}` placeholders of a hint text with the first key of each action
+fn key_hint(key: &str, actions: &[(&str, Action)], controls: &Bindings, res: &Resources) -> String {
+    let names: Vec<String> = actions
+        .iter()
+        .map(|&(_, action)| controls.key_name(action))
+        .collect();
+    let args: Vec<(&str, Arg)> = actions
+        .iter()
+        .zip(&names)
+        .map(|(&(placeholder, _), name)| (placeholder, name.as_str().into()))
+        .collect();
+    res.lang.format(key, &args)
+}
+
+pub fn render_briefing(mission: &Mission, controls: &Bindings, res: &Resources) {
     draw_text_centered(
-        &format!("{} {}", res.lang.t("mission"), mission.level_id),
+        &res.lang
+            .format("mission_number", &[("level", mission.level_id.into())]),
         -100.0,
         32,
         ORANGE,
         res,
     );
-    draw_text_centered(&mission.title, -50.0, 48, WHITE, res);
-    draw_text_centered(&mission.description, 0.0, 20, GRAY, res);
+    let title = mission_text(mission, &mission.title_key, &res.lang);
+    let description = mission_text(mission, &mission.description_key, &res.lang);
+    draw_text_centered(&title, -50.0, 48, WHITE, res);
+    draw_text_centered(&description, 0.0, 20, GRAY, res);
 
     draw_text_centered(res.lang.t("objectives"), 20.0, 24, GRAY, res);
 
-    let mut objectives = vec![format!(
-        "{} {} {}",
-        res.lang.t("obj_destroy_prefix"),
-        mission.target_kills,
-        res.lang.t("obj_enemies")
-    )];
+    let mut objectives = vec![res
+        .lang
+        .format("obj_destroy", &[("count", mission.target_kills.into())])];
     if mission.target_scrap > 0 {
-        objectives.push(format!(
-            "{} {} {}",
-            res.lang.t("obj_scrap_prefix"),
-            mission.target_scrap,
-            res.lang.t("obj_rust_piles")
-        ));
+        objectives.push(
+            res.lang
+                .format("obj_scrap", &[("count", mission.target_scrap.into())]),
+        );
     }
     if mission.target_rare_metal > 0 {
-        objectives.push(format!(
-            "{} {} {}",
-            res.lang.t("obj_gold_prefix"),
-            mission.target_rare_metal,
-            res.lang.t("obj_gold")
-        ));
+        objectives.push(
+            res.lang
+                .format("obj_gold", &[("count", mission.target_rare_metal.into())]),
+        );
     }
     let obj_text = objectives.join("\n");
     draw_text_centered(&obj_text, 70.0, 24, WHITE, res);
 
-    draw_text_centered(res.lang.t("press_space"), 200.0, 24, GREEN, res);
+    draw_text_centered(
+        &key_hint("press_space", &[("key", Action::Fire)], controls, res),
+        200.0,
+        24,
+        GREEN,
+        res,
+    );
+    let hint = key_hint(
+        "controls",
+        &[
+            ("thrust", Action::Thrust),
+            ("left", Action::RotateLeft),
+            ("right", Action::RotateRight),
+            ("fire", Action::Fire),
+        ],
+        controls,
+        res,
+    );
+    draw_text_centered(&hint, 240.0, 14, GRAY, res);
+}
+
+// Leaderboard of one difficulty, in columns: place, name, score, mission, date
+pub fn render_high_scores(
+    board: &[ScoreEntry],
+    difficulty: Difficulty,
+    controls: &Bindings,
+    res: &Resources,
+) {
+    draw_text_centered(res.lang.t("high_scores_title"), -260.0, 40, ORANGE, res);
+    draw_text_centered(
+        &format!("< {} >", res.lang.t(difficulty.name_key())),
+        -200.0,
+        20,
+        difficulty.color(),
+        res,
+    );
+
+    let center_x = screen_width() / 2.0;
+    let columns = [-400.0, -320.0, -60.0, 120.0, 220.0];
+    let row_y = |row: usize| screen_height() / 2.0 - 130.0 + row as f32 * 36.0;
+    let draw_row = |cells: [&str; 5], y: f32, size: f32, color: Color| {
+        for (cell, x) in cells.iter().zip(columns) {
+            draw_text_with_font(cell, center_x + x, y, size, color, res);
+        }
+    };
+
+    draw_row(
+        [
+            "#",
+            res.lang.t("hs_name"),
+            res.lang.t("hs_score"),
+            res.lang.t("hs_level"),
+            res.lang.t("hs_date"),
+        ],
+        row_y(0),
+        14.0,
+        GRAY,
+    );
+    if board.is_empty() {
+        draw_text_centered(res.lang.t("high_scores_empty"), -40.0, 20, GRAY, res);
+    }
+    for (i, entry) in board.iter().enumerate() {
+        // Unknown for a score carried over from before the leaderboards
+        let level = match entry.level {
+            0 => "-".to_string(),
+            level => level.to_string(),
+        };
+        let date = if entry.date.is_empty() {
+            "-"
+        } else {
+            &entry.date
+        };
+        draw_row(
+            [
+                &format!("{}.", i + 1),
+                &entry.name,
+                &entry.score.to_string(),
+                &level,
+                date,
+            ],
+            row_y(i + 1),
+            20.0,
+            if i == 0 { YELLOW } else { WHITE },
+        );
+    }
+
+    let hint = key_hint(
+        "high_scores_instructions",
+        &[
+            ("left", Action::NavigateLeft),
+            ("right", Action::NavigateRight),
+            ("confirm", Action::Confirm),
+        ],
+        controls,
+        res,
+    );
+    draw_text_centered(&hint, 300.0, 14, GRAY, res);
 }
 
-pub fn render_mission_success(mission: &Mission, res: &Resources) {
+pub fn render_mission_success(mission: &Mission, confirm_key: &str, res: &Resources) {
     draw_text_centered(res.lang.t("mission_complete"), -50.0, 40, GREEN, res);
     draw_text_centered(
-        &format!(
-            "{} {} {}",
-            res.lang.t("level_cleared_prefix"),
-            mission.level_id,
-            res.lang.t("level_cleared_suffix")
-        ),
+        &res.lang
+            .format("level_cleared", &[("level", mission.level_id.into())]),
         10.0,
         24,
         WHITE,
         res,
     );
-    draw_text_centered(res.lang.t("next_mission"), 100.0, 24, YELLOW, res);
+    draw_text_centered(
+        &res.lang
+            .format("next_mission", &[("key", confirm_key.into())]),
+        100.0,
+        24,
+        YELLOW,
+        res,
+    );
 }
 
-pub fn render_game_over(score: u32, res: &Resources) {
-    let high_score = load_score().high_score;
+// Upgrade shop in columns: upgrade, level, effect of the next level, price.
+// `selected` is a row of Upgrade::ALL, or the row after it that leaves the Hangar.
+pub fn render_hangar(game: &Game, selected: usize, controls: &Bindings, res: &Resources) {
+    draw_text_centered(res.lang.t("hangar_title"), -300.0, 40, ORANGE, res);
+    draw_text_centered(
+        &res.lang.format(
+            "hangar_funds",
+            &[
+                ("scrap", game.ship.scrap.into()),
+                ("gold", game.ship.rare_metal.into()),
+            ],
+        ),
+        -240.0,
+        20,
+        WHITE,
+        res,
+    );
+
+    let center_x = screen_width() / 2.0;
+    let columns = [-420.0, -100.0, 0.0, 240.0];
+    let row_y = |row: usize| screen_height() / 2.0 - 160.0 + row as f32 * 40.0;
+
+    for (row, upgrade) in Upgrade::ALL.into_iter().enumerate() {
+        let def = game.data.upgrades.def(upgrade);
+        let level = game.ship.upgrades.level(upgrade);
+        let cost = game.data.upgrades.next_cost(upgrade, &game.ship.upgrades);
+        let affordable = cost.is_some_and(|cost| {
+            game.ship.scrap >= cost.scrap && game.ship.rare_metal >= cost.rare_metal
+        });
+        let color = match (row == selected, affordable) {
+            (true, _) => YELLOW,
+            (false, true) => WHITE,
+            (false, false) => GRAY,
+        };
+
+        let value = if upgrade.is_percentage() {
+            format!("{}", (def.effect * 100.0).round())
+        } else {
+            format!("{}", def.effect)
+        };
+        let price = match cost {
+            Some(cost) => res.lang.format(
+                "hangar_cost",
+                &[
+                    ("scrap", cost.scrap.into()),
+                    ("gold", cost.rare_metal.into()),
+                ],
+            ),
+            None => res.lang.t("hangar_maxed").to_string(),
+        };
+        let cells = [
+            res.lang.t(upgrade.name_key()).to_string(),
+            res.lang.format(
+                "hangar_level",
+                &[
+                    ("level", level.into()),
+                    ("max", (def.levels.len() as u32).into()),
+                ],
+            ),
+            res.lang
+                .format(upgrade.effect_key(), &[("value", value.as_str().into())]),
+            price,
+        ];
+        for (cell, x) in cells.iter().zip(columns) {
+            draw_text_with_font(cell, center_x + x, row_y(row), 18.0, color, res);
+        }
+    }
+
+    let leave_selected = selected == Upgrade::ALL.len();
+    draw_text_with_font(
+        res.lang.t("hangar_launch"),
+        center_x + columns[0],
+        row_y(Upgrade::ALL.len()) + 18.0,
+        18.0,
+        if leave_selected { YELLOW } else { GRAY },
+        res,
+    );
+
+    let hint = key_hint(
+        "hangar_instructions",
+        &[
+            ("up", Action::NavigateUp),
+            ("down", Action::NavigateDown),
+            ("confirm", Action::Confirm),
+            ("back", Action::Pause),
+        ],
+        controls,
+        res,
+    );
+    draw_text_centered(&hint, 260.0, 14, GRAY, res);
+}
+
+// Key bindings in columns: action, primary key, secondary key
+pub fn render_controls(controls: &Bindings, cursor: &RebindCursor, res: &Resources) {
+    draw_text_centered(res.lang.t("controls_title"), -300.0, 40, ORANGE, res);
+
+    let center_x = screen_width() / 2.0;
+    let row_y = |row: usize| screen_height() / 2.0 - 220.0 + row as f32 * 24.0;
+
+    for (row, action) in Action::ALL.into_iter().enumerate() {
+        let selected = row == cursor.row;
+        let y = row_y(row);
+        let color = if selected { YELLOW } else { WHITE };
+        draw_text_with_font(
+            res.lang.t(action.name_key()),
+            center_x - 400.0,
+            y,
+            18.0,
+            color,
+            res,
+        );
+
+        let keys = controls.keys(action);
+        for slot in 0..BINDING_SLOTS {
+            let mut text = keys.get(slot).map_or("-".to_string(), |key| key.name());
+            if selected && slot == cursor.slot {
+                if cursor.waiting {
+                    text = "...".to_string();
+                }
+                text = format!("[{text}]");
+            }
+            let x = center_x + 60.0 + slot as f32 * 220.0;
+            draw_text_with_font(&text, x, y, 18.0, color, res);
+        }
+    }
+
+    let reset_selected = cursor.row == Action::ALL.len();
+    draw_text_with_font(
+        res.lang.t("controls_reset"),
+        center_x - 400.0,
+        row_y(Action::ALL.len()) + 18.0,
+        18.0,
+        if reset_selected { YELLOW } else { GRAY },
+        res,
+    );
+
+    let hint = if cursor.waiting {
+        let seconds = (cursor.wait_until - get_time()).ceil().max(0.0) as f32;
+        res.lang
+            .format("controls_waiting", &[("seconds", seconds.into())])
+    } else {
+        key_hint(
+            "controls_instructions",
+            &[
+                ("confirm", Action::Confirm),
+                ("erase", Action::Erase),
+                ("back", Action::Pause),
+            ],
+            controls,
+            res,
+        )
+    };
+    draw_text_centered(&hint, 260.0, 14, GRAY, res);
+}
+
+pub fn render_name_entry(score: u32, name: &str, controls: &Bindings, res: &Resources) {
+    draw_text_centered(res.lang.t("new_high_score"), -120.0, 40, YELLOW, res);
+    draw_text_centered(
+        &res.lang.format("final_score", &[("score", score.into())]),
+        -60.0,
+        24,
+        WHITE,
+        res,
+    );
+    draw_text_centered(res.lang.t("enter_name"), 0.0, 20, GRAY, res);
+
+    // Blinking cursor while there is room for more letters
+    let cursor = if name.chars().count() < PILOT_NAME_MAX_LEN && get_time() % 1.0 < 0.5 {
+        "_"
+    } else {
+        " "
+    };
+    draw_text_centered(&format!("{name}{cursor}"), 60.0, 32, ORANGE, res);
+
+    let hint = key_hint(
+        "name_entry_instructions",
+        &[("erase", Action::Erase), ("confirm", Action::Confirm)],
+        controls,
+        res,
+    );
+    draw_text_centered(&hint, 140.0, 14, GRAY, res);
+    draw_text_centered(res.lang.t("name_entry_pad"), 165.0, 14, GRAY, res);
+}
+
+pub fn render_game_over(
+    score: u32,
+    high_score: u32,
+    seed: u64,
+    has_replay: bool,
+    controls: &Bindings,
+    res: &Resources,
+) {
     draw_text_centered(res.lang.t("game_over"), -40.0, 48, RED, res);
     draw_text_centered(
-        &format!("{} {}", res.lang.t("final_score_prefix"), score),
+        &res.lang.format("final_score", &[("score", score.into())]),
         10.0,
         32,
         WHITE,
         res,
     );
     draw_text_centered(
-        &format!("{} {}", res.lang.t("high_score"), high_score),
+        &res.lang
+            .format("high_score", &[("score", high_score.into())]),
         60.0,
         24,
         YELLOW,
         res,
     );
+    draw_text_centered(
+        &res.lang
+            .format("seed", &[("seed", seed.to_string().as_str().into())]),
+        110.0,
+        16,
+        GRAY,
+        res,
+    );
+    if has_replay {
+        let hint = key_hint("press_replay", &[("replay", Action::Replay)], controls, res);
+        draw_text_centered(&hint, 160.0, 16, WHITE, res);
+    }
+}
+
+pub fn render_replay_overlay(exit_key: &str, res: &Resources) {
+    draw_text_with_font(
+        res.lang.t("replay"),
+        screen_width() - 220.0,
+        30.0,
+        24.0,
+        RED,
+        res,
+    );
+    draw_text_with_font(
+        &res.lang.format("replay_exit", &[("key", exit_key.into())]),
+        screen_width() - 220.0,
+        60.0,
+        14.0,
+        GRAY,
+        res,
+    );
+}
+
+pub fn render_notices(notices: &mut Notices, res: &Resources) {
+    let lines: Vec<String> = notices
+        .current()
+        .map(|(key, error)| res.lang.format(key, &[("error", error.into())]))
+        .collect();
+    for (i, line) in lines.iter().rev().enumerate() {
+        draw_text_with_font(
+            line,
+            20.0,
+            screen_height() - 20.0 - i as f32 * 24.0,
+            14.0,
+            RED,
+            res,
+        );
+    }
 }
 
-pub fn render_pause(res: &Resources) {
+pub fn render_pause(resume_key: &str, res: &Resources) {
     // Draw semi-transparent overlay
     draw_rectangle(
         0.0,
@@ -828,5 +1639,11 @@ pub fn render_pause(res: &Resources) {
 
     // Draw pause text
     draw_text_centered(res.lang.t("paused"), -20.0, 48, YELLOW, res);
-    draw_text_centered(res.lang.t("press_esc"), 30.0, 24, WHITE, res);
+    draw_text_centered(
+        &res.lang.format("press_esc", &[("key", resume_key.into())]),
+        30.0,
+        24,
+        WHITE,
+        res,
+    );
 }
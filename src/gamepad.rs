@@ -0,0 +1,177 @@
+use crate::input::Action;
+
+// Stick positions closer to the center than this count as centered
+#[cfg(feature = "gamepad")]
+const DEADZONE: f32 = 0.2;
+// How far the left stick has to be pushed to move through a menu
+const NAVIGATE_THRESHOLD: f32 = 0.6;
+
+// Gamepad state for the frame, read through gilrs when the `gamepad` feature is
+// enabled. Without it there are never any gamepads and everything reads as idle.
+//
+// Layout: left stick turns (x) and thrusts (pushed up, as far as it is pushed;
+// pulled down for reverse), the left trigger also thrusts, the right trigger
+// fires and West (X) brakes. In menus the D-pad or
+// the left stick navigates, South (A) confirms, Start pauses and East (B) goes back;
+// West (X) erases and North (Y) watches the replay on the game over screen. A name
+// is spelled with the D-pad: up and down change the last letter, right adds one.
+#[derive(Default)]
+pub struct Gamepads {
+    #[cfg(feature = "gamepad")]
+    gilrs: Option<gilrs::Gilrs>,
+    #[cfg(feature = "gamepad")]
+    active: Option<gilrs::GamepadId>, // The pad that was used last
+    pressed: Vec<Action>,            // Actions triggered this frame
+    stick_direction: Option<Action>, // Menu direction the stick is held in
+}
+
+// Analog controls of the active gamepad
+#[derive(Default, Clone, Copy)]
+pub struct PadState {
+    pub turn: f32,    // -1 to 1
+    pub thrust: f32,  // 0 to 1
+    pub reverse: f32, // 0 to 1
+    pub brake: bool,
+    pub fire: bool,
+}
+
+#[cfg(feature = "gamepad")]
+fn deadzone(value: f32) -> f32 {
+    if value.abs() < DEADZONE {
+        0.0
+    } else {
+        // Rescaled so the output still starts at 0 right outside the deadzone
+        value.signum() * (value.abs() - DEADZONE) / (1.0 - DEADZONE)
+    }
+}
+
+impl Gamepads {
+    #[cfg(feature = "gamepad")]
+    pub fn new() -> Self {
+        let gilrs = match gilrs::Gilrs::new() {
+            Ok(gilrs) => Some(gilrs),
+            Err(e) => {
+                macroquad::logging::warn!("Gamepads are not available: {}", e);
+                None
+            }
+        };
+        Self {
+            gilrs,
+            ..Default::default()
+        }
+    }
+
+    #[cfg(not(feature = "gamepad"))]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Reads the gamepad events of the frame; call once per frame before any queries
+    pub fn update(&mut self) {
+        self.pressed.clear();
+        #[cfg(feature = "gamepad")]
+        self.read_events();
+
+        // Pushing the stick past the threshold acts like one D-pad press
+        let direction = self.stick().and_then(|(x, y)| {
+            if y > NAVIGATE_THRESHOLD {
+                Some(Action::NavigateUp)
+            } else if y < -NAVIGATE_THRESHOLD {
+                Some(Action::NavigateDown)
+            } else if x < -NAVIGATE_THRESHOLD {
+                Some(Action::NavigateLeft)
+            } else if x > NAVIGATE_THRESHOLD {
+                Some(Action::NavigateRight)
+            } else {
+                None
+            }
+        });
+        if direction.is_some() && direction != self.stick_direction {
+            self.pressed.extend(direction);
+        }
+        self.stick_direction = direction;
+    }
+
+    #[cfg(feature = "gamepad")]
+    fn read_events(&mut self) {
+        use gilrs::{Button, EventType};
+
+        let Some(gilrs) = self.gilrs.as_mut() else {
+            return;
+        };
+        while let Some(event) = gilrs.next_event() {
+            match event.event {
+                EventType::Disconnected if self.active == Some(event.id) => {
+                    self.active = None;
+                    continue;
+                }
+                EventType::Connected | EventType::Disconnected | EventType::Dropped => continue,
+                _ => self.active = Some(event.id),
+            }
+            if let EventType::ButtonPressed(button, _) = event.event {
+                let action = match button {
+                    Button::South => Some(Action::Confirm),
+                    Button::Start | Button::East => Some(Action::Pause),
+                    Button::RightTrigger2 => Some(Action::Fire),
+                    Button::West => Some(Action::Erase),
+                    Button::North => Some(Action::Replay),
+                    Button::DPadUp => Some(Action::NavigateUp),
+                    Button::DPadDown => Some(Action::NavigateDown),
+                    Button::DPadLeft => Some(Action::NavigateLeft),
+                    Button::DPadRight => Some(Action::NavigateRight),
+                    _ => None,
+                };
+                self.pressed.extend(action);
+            }
+        }
+    }
+
+    #[cfg(feature = "gamepad")]
+    fn active_pad(&self) -> Option<gilrs::Gamepad<'_>> {
+        self.gilrs.as_ref()?.connected_gamepad(self.active?)
+    }
+
+    // Left stick of the active pad, y pointing up, without the deadzone applied
+    #[cfg(feature = "gamepad")]
+    fn stick(&self) -> Option<(f32, f32)> {
+        let pad = self.active_pad()?;
+        Some((
+            pad.value(gilrs::Axis::LeftStickX),
+            pad.value(gilrs::Axis::LeftStickY),
+        ))
+    }
+
+    #[cfg(not(feature = "gamepad"))]
+    fn stick(&self) -> Option<(f32, f32)> {
+        None
+    }
+
+    #[cfg(feature = "gamepad")]
+    pub fn state(&self) -> PadState {
+        use gilrs::{Axis, Button};
+
+        let Some(pad) = self.active_pad() else {
+            return PadState::default();
+        };
+        let trigger = |button| pad.button_data(button).map_or(0.0, |data| data.value());
+        PadState {
+            turn: deadzone(pad.value(Axis::LeftStickX)),
+            thrust: deadzone(pad.value(Axis::LeftStickY))
+                .max(0.0)
+                .max(trigger(Button::LeftTrigger2)),
+            reverse: (-deadzone(pad.value(Axis::LeftStickY))).max(0.0),
+            brake: pad.is_pressed(Button::West),
+            fire: trigger(Button::RightTrigger2) > 0.5,
+        }
+    }
+
+    #[cfg(not(feature = "gamepad"))]
+    pub fn state(&self) -> PadState {
+        PadState::default()
+    }
+
+    // Whether a gamepad button triggered the action this frame
+    pub fn is_pressed(&self, action: Action) -> bool {
+        self.pressed.contains(&action)
+    }
+}
@@ -0,0 +1,344 @@
+use macroquad::prelude::*;
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
+
+// What the player wants to do, independent of the key that does it
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Thrust,
+    ReverseThrust,
+    Brake, // Inertial dampeners
+    RotateLeft,
+    RotateRight,
+    ThrustScreenUp, // Screen-relative thrust of the Mouse aim + WASD scheme
+    ThrustScreenDown,
+    ThrustScreenLeft,
+    ThrustScreenRight,
+    Fire,
+    Pause, // Also leaves screens that have a way back
+    Confirm,
+    Erase,  // Deletes the last letter of a name, clears a key binding
+    Replay, // Watches the last run from the game over screen
+    NavigateUp,
+    NavigateDown,
+    NavigateLeft,
+    NavigateRight,
+}
+
+impl Action {
+    // In the order of the rebinding screen
+    pub const ALL: [Action; 18] = [
+        Action::Thrust,
+        Action::ReverseThrust,
+        Action::Brake,
+        Action::RotateLeft,
+        Action::RotateRight,
+        Action::ThrustScreenUp,
+        Action::ThrustScreenDown,
+        Action::ThrustScreenLeft,
+        Action::ThrustScreenRight,
+        Action::Fire,
+        Action::Pause,
+        Action::Confirm,
+        Action::Erase,
+        Action::Replay,
+        Action::NavigateUp,
+        Action::NavigateDown,
+        Action::NavigateLeft,
+        Action::NavigateRight,
+    ];
+
+    // Localization key of the action's name
+    pub fn name_key(self) -> &'static str {
+        match self {
+            Action::Thrust => "action_thrust",
+            Action::ReverseThrust => "action_reverse_thrust",
+            Action::Brake => "action_brake",
+            Action::RotateLeft => "action_rotate_left",
+            Action::RotateRight => "action_rotate_right",
+            Action::ThrustScreenUp => "action_thrust_screen_up",
+            Action::ThrustScreenDown => "action_thrust_screen_down",
+            Action::ThrustScreenLeft => "action_thrust_screen_left",
+            Action::ThrustScreenRight => "action_thrust_screen_right",
+            Action::Fire => "action_fire",
+            Action::Pause => "action_pause",
+            Action::Confirm => "action_confirm",
+            Action::Erase => "action_erase",
+            Action::Replay => "action_replay",
+            Action::NavigateUp => "action_navigate_up",
+            Action::NavigateDown => "action_navigate_down",
+            Action::NavigateLeft => "action_navigate_left",
+            Action::NavigateRight => "action_navigate_right",
+        }
+    }
+
+    // Actions that must keep a key, or the menus could not be used to fix it
+    fn is_required(self) -> bool {
+        matches!(
+            self,
+            Action::Confirm | Action::NavigateUp | Action::NavigateDown | Action::Pause
+        )
+    }
+}
+
+// Keys each action can be bound to (a primary and a secondary one)
+pub const BINDING_SLOTS: usize = 2;
+
+// Keys that can be bound, stored in the settings file by name
+const BINDABLE_KEYS: &[KeyCode] = &[
+    KeyCode::Up,
+    KeyCode::Down,
+    KeyCode::Left,
+    KeyCode::Right,
+    KeyCode::Space,
+    KeyCode::Enter,
+    KeyCode::Escape,
+    KeyCode::Tab,
+    KeyCode::Backspace,
+    KeyCode::LeftShift,
+    KeyCode::RightShift,
+    KeyCode::LeftControl,
+    KeyCode::RightControl,
+    KeyCode::LeftAlt,
+    KeyCode::RightAlt,
+    KeyCode::Insert,
+    KeyCode::Delete,
+    KeyCode::Home,
+    KeyCode::End,
+    KeyCode::PageUp,
+    KeyCode::PageDown,
+    KeyCode::A,
+    KeyCode::B,
+    KeyCode::C,
+    KeyCode::D,
+    KeyCode::E,
+    KeyCode::F,
+    KeyCode::G,
+    KeyCode::H,
+    KeyCode::I,
+    KeyCode::J,
+    KeyCode::K,
+    KeyCode::L,
+    KeyCode::M,
+    KeyCode::N,
+    KeyCode::O,
+    KeyCode::P,
+    KeyCode::Q,
+    KeyCode::R,
+    KeyCode::S,
+    KeyCode::T,
+    KeyCode::U,
+    KeyCode::V,
+    KeyCode::W,
+    KeyCode::X,
+    KeyCode::Y,
+    KeyCode::Z,
+    KeyCode::Key0,
+    KeyCode::Key1,
+    KeyCode::Key2,
+    KeyCode::Key3,
+    KeyCode::Key4,
+    KeyCode::Key5,
+    KeyCode::Key6,
+    KeyCode::Key7,
+    KeyCode::Key8,
+    KeyCode::Key9,
+    KeyCode::Kp0,
+    KeyCode::Kp1,
+    KeyCode::Kp2,
+    KeyCode::Kp3,
+    KeyCode::Kp4,
+    KeyCode::Kp5,
+    KeyCode::Kp6,
+    KeyCode::Kp7,
+    KeyCode::Kp8,
+    KeyCode::Kp9,
+    KeyCode::KpEnter,
+    KeyCode::KpAdd,
+    KeyCode::KpSubtract,
+    KeyCode::KpMultiply,
+    KeyCode::KpDivide,
+    KeyCode::KpDecimal,
+    KeyCode::Comma,
+    KeyCode::Period,
+    KeyCode::Slash,
+    KeyCode::Semicolon,
+    KeyCode::Apostrophe,
+    KeyCode::Minus,
+    KeyCode::Equal,
+    KeyCode::LeftBracket,
+    KeyCode::RightBracket,
+    KeyCode::Backslash,
+    KeyCode::GraveAccent,
+    KeyCode::F1,
+    KeyCode::F2,
+    KeyCode::F3,
+    KeyCode::F4,
+    KeyCode::F5,
+    KeyCode::F6,
+    KeyCode::F7,
+    KeyCode::F8,
+    KeyCode::F9,
+    KeyCode::F10,
+    KeyCode::F11,
+    KeyCode::F12,
+];
+
+// A bindable key, saved as its name ("Up", "Space", "W", ...)
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Key(pub KeyCode);
+
+impl Key {
+    pub fn name(self) -> String {
+        format!("{:?}", self.0)
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        BINDABLE_KEYS
+            .iter()
+            .map(|&code| Key(code))
+            .find(|key| key.name() == name)
+    }
+
+    // A bindable key pressed this frame
+    pub fn pressed() -> Option<Self> {
+        get_keys_pressed()
+            .into_iter()
+            .find(|code| BINDABLE_KEYS.contains(code))
+            .map(Key)
+    }
+}
+
+impl Serialize for Key {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for Key {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Key::from_name(&name).ok_or_else(|| D::Error::custom(format!("unknown key `{name}`")))
+    }
+}
+
+// Keys bound to every action. Actions missing from the settings file keep
+// their default keys; key names this version does not know are skipped.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(
+    from = "BTreeMap<Action, Vec<String>>",
+    into = "BTreeMap<Action, Vec<Key>>"
+)]
+pub struct Bindings {
+    keys: BTreeMap<Action, Vec<Key>>,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        let keys = Action::ALL
+            .into_iter()
+            .map(|action| {
+                let codes: &[KeyCode] = match action {
+                    Action::Thrust => &[KeyCode::Up],
+                    Action::ReverseThrust => &[KeyCode::Down],
+                    Action::Brake => &[KeyCode::LeftShift],
+                    Action::RotateLeft => &[KeyCode::Left],
+                    Action::RotateRight => &[KeyCode::Right],
+                    Action::ThrustScreenUp => &[KeyCode::W],
+                    Action::ThrustScreenDown => &[KeyCode::S],
+                    Action::ThrustScreenLeft => &[KeyCode::A],
+                    Action::ThrustScreenRight => &[KeyCode::D],
+                    Action::Fire => &[KeyCode::Space],
+                    Action::Pause => &[KeyCode::Escape],
+                    Action::Confirm => &[KeyCode::Enter, KeyCode::KpEnter],
+                    Action::Erase => &[KeyCode::Backspace, KeyCode::Delete],
+                    Action::Replay => &[KeyCode::R],
+                    Action::NavigateUp => &[KeyCode::Up],
+                    Action::NavigateDown => &[KeyCode::Down],
+                    Action::NavigateLeft => &[KeyCode::Left],
+                    Action::NavigateRight => &[KeyCode::Right],
+                };
+                (action, codes.iter().map(|&code| Key(code)).collect())
+            })
+            .collect();
+        Self { keys }
+    }
+}
+
+impl From<BTreeMap<Action, Vec<String>>> for Bindings {
+    fn from(saved: BTreeMap<Action, Vec<String>>) -> Self {
+        let mut bindings = Bindings::default();
+        for (action, names) in saved {
+            let mut keys: Vec<Key> = names
+                .iter()
+                .filter_map(|name| {
+                    let key = Key::from_name(name);
+                    if key.is_none() {
+                        warn!("Unknown key `{}` bound to {:?}, skipped", name, action);
+                    }
+                    key
+                })
+                .collect();
+            keys.truncate(BINDING_SLOTS);
+            // An action left without keys only because none of them were known
+            // keeps its defaults, like actions the menus need
+            if !keys.is_empty() || (names.is_empty() && !action.is_required()) {
+                bindings.keys.insert(action, keys);
+            }
+        }
+        bindings
+    }
+}
+
+impl From<Bindings> for BTreeMap<Action, Vec<Key>> {
+    fn from(bindings: Bindings) -> Self {
+        bindings.keys
+    }
+}
+
+impl Bindings {
+    pub fn keys(&self, action: Action) -> &[Key] {
+        self.keys.get(&action).map_or(&[], |keys| keys.as_slice())
+    }
+
+    pub fn is_down(&self, action: Action) -> bool {
+        self.keys(action).iter().any(|key| is_key_down(key.0))
+    }
+
+    pub fn is_pressed(&self, action: Action) -> bool {
+        self.keys(action).iter().any(|key| is_key_pressed(key.0))
+    }
+
+    // Name of the action's first key, for on-screen hints
+    pub fn key_name(&self, action: Action) -> String {
+        self.keys(action)
+            .first()
+            .map_or_else(|| "-".to_string(), |key| key.name().to_uppercase())
+    }
+
+    // Puts `key` into a binding slot. Binding a key the action already has
+    // moves it to that slot instead of listing it twice.
+    pub fn bind(&mut self, action: Action, slot: usize, key: Key) {
+        let keys = self.keys.entry(action).or_default();
+        keys.retain(|&k| k != key);
+        if slot < keys.len() {
+            keys[slot] = key;
+        } else {
+            keys.push(key);
+        }
+        keys.truncate(BINDING_SLOTS);
+    }
+
+    // Removes the key in a binding slot, unless it is the last key of an
+    // action the menus need. Returns whether a key was removed.
+    pub fn clear(&mut self, action: Action, slot: usize) -> bool {
+        let keys = self.keys.entry(action).or_default();
+        if slot >= keys.len() || (action.is_required() && keys.len() == 1) {
+            return false;
+        }
+        keys.remove(slot);
+        true
+    }
+}
@@ -0,0 +1,20 @@
+pub mod components;
+pub mod data;
+pub mod draw;
+pub mod enemies;
+pub mod events;
+pub mod fonts;
+pub mod game;
+pub mod gamepad;
+pub mod input;
+pub mod localization;
+pub mod loot;
+pub mod missions;
+pub mod replay;
+pub mod resources;
+pub mod settings;
+pub mod spatial;
+pub mod storage;
+pub mod systems;
+pub mod tuning;
+pub mod upgrades;
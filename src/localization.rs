@@ -1,210 +1,293 @@
-use std::collections::HashMap;
-
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+use crate::data::{load_json, DataError};
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::Path;
+
+// One JSON file per language (key -> text), named after Language::code
+pub const LANG_DIR: &str = "assets/lang";
+
+// Saved in the settings file by code
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
 pub enum Language {
+    #[serde(rename = "en")]
     English,
+    #[serde(rename = "ru")]
     Russian,
+    #[serde(rename = "de")]
     German,
 }
 
-pub struct Localization {
-    pub current_lang: Language,
-    en_dict: HashMap<&'static str, &'static str>,
-    ru_dict: HashMap<&'static str, &'static str>,
-    de_dict: HashMap<&'static str, &'static str>,
-}
-
-impl Localization {
-    pub fn new() -> Self {
-        let mut en = HashMap::new();
-        let mut ru = HashMap::new();
-        let mut de = HashMap::new();
-
-        // --- MENU ---
-        en.insert("press_enter", "Press [ENTER] to Start");
-        ru.insert("press_enter", "Нажми [ENTER] для старта");
+impl Language {
+    pub const ALL: [Language; 3] = [Language::English, Language::Russian, Language::German];
 
-        en.insert("difficulty", "DIFFICULTY (Left/Right Arrows):");
-        ru.insert("difficulty", "СЛОЖНОСТЬ (Стрелки Влево/Вправо):");
+    pub fn code(self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::Russian => "ru",
+            Language::German => "de",
+        }
+    }
 
-        en.insert("diff_nebula", "NEBULA (Easy)");
-        ru.insert("diff_nebula", "ТУМАННОСТЬ (Легко)");
+    // Plural forms the language distinguishes, as named in the translation files
+    pub fn plural_forms(self) -> &'static [&'static str] {
+        match self {
+            Language::English | Language::German => &["one", "other"],
+            Language::Russian => &["one", "few", "many"],
+        }
+    }
 
-        en.insert("diff_supernova", "SUPERNOVA (Normal)");
-        ru.insert("diff_supernova", "СВЕРХНОВАЯ (Норма)");
+    // Plural form to use for `n` (CLDR rules for integers)
+    pub fn plural_form(self, n: i64) -> &'static str {
+        let n = n.unsigned_abs();
+        match self {
+            Language::English | Language::German => {
+                if n == 1 {
+                    "one"
+                } else {
+                    "other"
+                }
+            }
+            Language::Russian => {
+                if n % 10 == 1 && n % 100 != 11 {
+                    "one"
+                } else if (2..=4).contains(&(n % 10)) && !(12..=14).contains(&(n % 100)) {
+                    "few"
+                } else {
+                    "many"
+                }
+            }
+        }
+    }
+}
 
-        en.insert("diff_blackhole", "BLACK HOLE (Hard)");
-        ru.insert("diff_blackhole", "ЧЕРНАЯ ДЫРА (Сложно)");
+// A translation: plain text, or one text per plural form picked by the `count` argument.
+// Texts may contain `{name}` placeholders that Localization::format fills in.
+#[derive(Deserialize, Clone)]
+#[serde(untagged)]
+pub enum Text {
+    Plain(String),
+    Plural(PluralText),
+}
 
-        en.insert("change_lang", "Press [L] to change Language");
-        ru.insert("change_lang", "Нажми [L] для смены языка");
+#[derive(Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct PluralText {
+    pub one: Option<String>,
+    pub few: Option<String>,
+    pub many: Option<String>,
+    pub other: Option<String>,
+}
 
-        // --- BRIEFING ---
-        en.insert("mission", "MISSION");
-        ru.insert("mission", "МИССИЯ");
+impl PluralText {
+    pub fn get(&self, form: &str) -> Option<&str> {
+        match form {
+            "one" => self.one.as_deref(),
+            "few" => self.few.as_deref(),
+            "many" => self.many.as_deref(),
+            "other" => self.other.as_deref(),
+            _ => None,
+        }
+    }
 
-        en.insert("objectives", "OBJECTIVES:");
-        ru.insert("objectives", "ЦЕЛИ:");
+    // Used when a form is missing: `other`, then the last form given
+    fn fallback(&self) -> &str {
+        self.other
+            .as_deref()
+            .or(self.many.as_deref())
+            .or(self.few.as_deref())
+            .or(self.one.as_deref())
+            .unwrap_or("")
+    }
+}
 
-        en.insert("obj_destroy_prefix", "- Destroy");
-        ru.insert("obj_destroy_prefix", "- Уничтожить");
+// A named value for Localization::format
+#[derive(Clone, Copy)]
+pub enum Arg<'a> {
+    Number(i64),
+    Text(&'a str),
+}
 
-        en.insert("obj_scrap_prefix", "- Collect");
-        ru.insert("obj_scrap_prefix", "- Собрать");
+impl fmt::Display for Arg<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Arg::Number(n) => write!(f, "{n}"),
+            Arg::Text(s) => write!(f, "{s}"),
+        }
+    }
+}
 
-        en.insert("obj_gold_prefix", "- Collect");
-        ru.insert("obj_gold_prefix", "- Собрать");
+impl From<i64> for Arg<'_> {
+    fn from(n: i64) -> Self {
+        Arg::Number(n)
+    }
+}
 
-        en.insert("obj_enemies", "Enemies");
-        ru.insert("obj_enemies", "Врагов");
+impl From<i32> for Arg<'_> {
+    fn from(n: i32) -> Self {
+        Arg::Number(n as i64)
+    }
+}
 
-        en.insert("obj_rust_piles", "Rust Piles");
-        ru.insert("obj_rust_piles", "Куч Лома");
+impl From<u32> for Arg<'_> {
+    fn from(n: u32) -> Self {
+        Arg::Number(n as i64)
+    }
+}
 
-        en.insert("obj_gold", "Gold");
-        ru.insert("obj_gold", "Золота");
+// Rounded to whole numbers, like the HP and shield values on the HUD
+impl From<f32> for Arg<'_> {
+    fn from(n: f32) -> Self {
+        Arg::Number(n.round() as i64)
+    }
+}
 
-        en.insert("press_space", "Press [SPACE] to Launch");
-        ru.insert("press_space", "Нажми [ПРОБЕЛ] для запуска");
+impl<'a> From<&'a str> for Arg<'a> {
+    fn from(s: &'a str) -> Self {
+        Arg::Text(s)
+    }
+}
 
-        // --- GAMEPLAY / UI ---
-        en.insert("mission_complete", "MISSION COMPLETE!");
-        ru.insert("mission_complete", "МИССИЯ ВЫПОЛНЕНА!");
+pub struct Localization {
+    pub current_lang: Language,
+    dicts: HashMap<Language, HashMap<String, Text>>,
+    warned: RefCell<HashSet<(Language, String)>>, // Missing keys already reported
+}
 
-        en.insert("level_cleared_prefix", "Level");
-        ru.insert("level_cleared_prefix", "Уровень");
+impl Localization {
+    // A language file that fails to load is logged and left empty,
+    // so all of its text falls back to English.
+    pub fn load(dir: &Path) -> Self {
+        let mut dicts = HashMap::new();
+        for lang in Language::ALL {
+            let dict = load_language(dir, lang).unwrap_or_else(|e| {
+                error!("Failed to load translation: {}", e);
+                HashMap::new()
+            });
+            dicts.insert(lang, dict);
+        }
 
-        en.insert("level_cleared_suffix", "Cleared");
-        ru.insert("level_cleared_suffix", "Пройден");
-
-        en.insert("next_mission", "Press [ENTER] for Next Mission");
-        ru.insert("next_mission", "Нажми [ENTER] для след. миссии");
-
-        en.insert("game_over", "GAME OVER");
-        ru.insert("game_over", "ИГРА ОКОНЧЕНА");
-
-        en.insert("final_score_prefix", "Final Score:");
-        ru.insert("final_score_prefix", "Итоговый счет:");
-
-        en.insert("high_score", "HIGH SCORE:");
-        ru.insert("high_score", "РЕКОРД:");
-
-        en.insert("press_esc", "Press [ESC] to Resume");
-        ru.insert("press_esc", "Нажми [ESC] для продолжения");
-
-        en.insert("controls", "ARROWS to move | SPACE to shoot");
-        ru.insert("controls", "СТРЕЛКИ для движения | ПРОБЕЛ для стрельбы");
-
-        en.insert("paused", "PAUSED");
-        ru.insert("paused", "ПАУЗА");
-
-        // --- IN-GAME UI ---
-        en.insert("score", "SCORE:");
-        ru.insert("score", "СЧЕТ:");
-
-        en.insert("hp", "HP:");
-        ru.insert("hp", "ЗДОРОВЬЕ:");
-
-        en.insert("shield", "SHIELD:");
-        ru.insert("shield", "ЩИТ:");
-
-        en.insert("defeated", "Defeated:");
-        ru.insert("defeated", "Побеждено:");
-
-        en.insert("rust", "Rust:");
-        ru.insert("rust", "Лом:");
-
-        en.insert("gold", "Gold:");
-        ru.insert("gold", "Золото:");
-
-        en.insert("resources", "Resources:");
-        ru.insert("resources", "Ресурсы:");
-
-        // --- MENU ITEMS ---
-        en.insert("menu_start", "START");
-        ru.insert("menu_start", "НАЧАТЬ");
-
-        en.insert("menu_difficulty", "Difficulty");
-        ru.insert("menu_difficulty", "Сложность");
-
-        en.insert("menu_language", "Language");
-        ru.insert("menu_language", "Язык");
-
-        en.insert("lang_english", "English");
-        ru.insert("lang_english", "Английский");
-
-        en.insert("lang_russian", "Russian");
-        ru.insert("lang_russian", "Русский");
-
-        en.insert(
-            "menu_instructions",
-            "UP/DOWN: Select  LEFT/RIGHT: Change  ENTER: Confirm",
-        );
-        ru.insert(
-            "menu_instructions",
-            "ВВЕРХ/ВНИЗ: Выбрать  ВЛЕВО/ВПРАВО: Изменить  ENTER: Подтвердить",
-        );
-        de.insert(
-            "menu_instructions",
-            "OBEN/UNTEN: Auswählen  LINKS/RECHTS: Ändern  ENTER: Bestätigen",
-        );
-
-        // German translations
-        de.insert("press_enter", "Drücke [ENTER] zum Starten");
-        de.insert("difficulty", "SCHWIERIGKEIT (Links/Rechts Pfeile):");
-        de.insert("diff_nebula", "NEBEL (Einfach)");
-        de.insert("diff_supernova", "SUPERNOVA (Normal)");
-        de.insert("diff_blackhole", "SCHWARZES LOCH (Schwer)");
-        de.insert("change_lang", "Drücke [L] um Sprache zu ändern");
-        de.insert("mission", "MISSION");
-        de.insert("objectives", "ZIELE:");
-        de.insert("obj_destroy_prefix", "- Zerstöre");
-        de.insert("obj_scrap_prefix", "- Sammle");
-        de.insert("obj_gold_prefix", "- Sammle");
-        de.insert("obj_enemies", "Feinde");
-        de.insert("obj_rust_piles", "Rosthaufen");
-        de.insert("obj_gold", "Gold");
-        de.insert("press_space", "Drücke [LEERTASTE] zum Starten");
-        de.insert("mission_complete", "MISSION ERFOLGREICH!");
-        de.insert("level_cleared_prefix", "Level");
-        de.insert("level_cleared_suffix", "Geschafft");
-        de.insert("next_mission", "Drücke [ENTER] für nächste Mission");
-        de.insert("game_over", "SPIEL VORBEI");
-        de.insert("final_score_prefix", "Endpunktzahl:");
-        de.insert("high_score", "REKORD:");
-        de.insert("press_esc", "Drücke [ESC] zum Fortsetzen");
-        de.insert("controls", "PFEILE zum Bewegen | LEERTASTE zum Schießen");
-        de.insert("paused", "PAUSIERT");
-        de.insert("score", "PUNKTE:");
-        de.insert("hp", "LP:");
-        de.insert("shield", "SCHILD:");
-        de.insert("defeated", "Besiegt:");
-        de.insert("rust", "Rost:");
-        de.insert("gold", "Gold:");
-        de.insert("resources", "Ressourcen:");
-        de.insert("menu_start", "STARTEN");
-        de.insert("menu_difficulty", "Schwierigkeit");
-        de.insert("menu_language", "Sprache");
-        de.insert("lang_english", "Englisch");
-        de.insert("lang_russian", "Russisch");
-        de.insert("lang_german", "Deutsch");
-
-        Self {
+        let lang = Self {
             current_lang: Language::English,
-            en_dict: en,
-            ru_dict: ru,
-            de_dict: de,
+            dicts,
+            warned: RefCell::new(HashSet::new()),
+        };
+        for l in Language::ALL {
+            let missing = lang.missing_keys(l);
+            if !missing.is_empty() {
+                warn!(
+                    "{}.json is missing {} key(s), English is used instead: {}",
+                    l.code(),
+                    missing.len(),
+                    missing.join(", ")
+                );
+            }
+        }
+        lang
+    }
+
+    // Text for the current language, as is. Plural texts give their `other` form.
+    pub fn t<'a>(&'a self, key: &'a str) -> &'a str {
+        match self.find(key) {
+            Some((_, Text::Plain(text))) => text,
+            Some((_, Text::Plural(plural))) => plural.fallback(),
+            None => key,
         }
     }
 
-    pub fn t(&self, key: &str) -> &str {
-        let dict = match self.current_lang {
-            Language::English => &self.en_dict,
-            Language::Russian => &self.ru_dict,
-            Language::German => &self.de_dict,
+    // Text for the current language with `{name}` placeholders replaced by `args`.
+    // If the text has plural forms, the `count` argument picks one. A `{name:key}`
+    // placeholder is replaced by the text of `key` formatted with the number `name`
+    // as its `count`, so one sentence can hold several counted words.
+    pub fn format(&self, key: &str, args: &[(&str, Arg)]) -> String {
+        let template = match self.find(key) {
+            Some((_, Text::Plain(text))) => text.as_str(),
+            Some((lang, Text::Plural(plural))) => {
+                let count = args.iter().find_map(|(name, arg)| match arg {
+                    Arg::Number(n) if *name == "count" => Some(*n),
+                    _ => None,
+                });
+                count
+                    .and_then(|n| plural.get(lang.plural_form(n)))
+                    .unwrap_or_else(|| plural.fallback())
+            }
+            None => key,
         };
+        self.fill(template, args)
+    }
+
+    // Replaces `{name}` and `{name:key}` placeholders; unknown names are left in place
+    // so they stand out
+    fn fill(&self, template: &str, args: &[(&str, Arg)]) -> String {
+        let mut out = String::with_capacity(template.len());
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            out.push_str(&rest[..start]);
+            let after = &rest[start + 1..];
+            let Some(end) = after.find('}') else {
+                rest = &rest[start..];
+                break;
+            };
+            let placeholder = &after[..end];
+            let (name, counted_key) = match placeholder.split_once(':') {
+                Some((name, key)) => (name, Some(key)),
+                None => (placeholder, None),
+            };
+            match (
+                args.iter().find(|(arg_name, _)| *arg_name == name),
+                counted_key,
+            ) {
+                (Some((_, arg)), None) => out.push_str(&arg.to_string()),
+                (Some((_, Arg::Number(n))), Some(key)) => {
+                    out.push_str(&self.format(key, &[("count", Arg::Number(*n))]));
+                }
+                _ => out.push_str(&rest[start..start + end + 2]),
+            }
+            rest = &after[end + 1..];
+        }
+        out.push_str(rest);
+        out
+    }
+
+    // Looks a key up in the current language, falling back to English (with a
+    // warning the first time). Also returns the language the text is in.
+    fn find(&self, key: &str) -> Option<(Language, &Text)> {
+        if let Some(text) = self.lookup(self.current_lang, key) {
+            return Some((self.current_lang, text));
+        }
+        if self
+            .warned
+            .borrow_mut()
+            .insert((self.current_lang, key.to_string()))
+        {
+            warn!(
+                "Missing {} translation for \"{}\"",
+                self.current_lang.code(),
+                key
+            );
+        }
+        self.lookup(Language::English, key)
+            .map(|text| (Language::English, text))
+    }
 
-        dict.get(key).unwrap_or(&"MISSING_TEXT")
+    fn lookup(&self, lang: Language, key: &str) -> Option<&Text> {
+        self.dicts.get(&lang)?.get(key)
+    }
+
+    // Keys that English has and `lang` does not, sorted
+    pub fn missing_keys(&self, lang: Language) -> Vec<String> {
+        let Some(english) = self.dicts.get(&Language::English) else {
+            return Vec::new();
+        };
+        let mut missing: Vec<String> = english
+            .keys()
+            .filter(|key| self.lookup(lang, key).is_none())
+            .cloned()
+            .collect();
+        missing.sort();
+        missing
     }
 
     pub fn cycle_lang(&mut self) {
@@ -215,3 +298,7 @@ impl Localization {
         };
     }
 }
+
+pub fn load_language(dir: &Path, lang: Language) -> Result<HashMap<String, Text>, DataError> {
+    load_json(&dir.join(format!("{}.json", lang.code())))
+}
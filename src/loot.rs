@@ -0,0 +1,193 @@
+use crate::components::{Difficulty, LootType};
+use crate::data::Validate;
+use crate::systems::LootSource;
+use macroquad::rand::RandGenerator;
+use serde::Deserialize;
+
+// Drop tables from loot.json. Every source has a list of weighted entries;
+// an entry's chance is its weight divided by the sum of the table's weights.
+#[derive(Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct LootTables {
+    pub luck: Luck,
+    pub asteroid: Vec<LootEntry>,
+    pub rare_asteroid: Vec<LootEntry>,
+    pub enemy: Vec<LootEntry>,
+}
+
+// Difficulty luck in percent: a drop's weight and its amount are multiplied by
+// (100 + luck) / 100. The weights only matter in tables with a "nothing" entry,
+// where positive luck makes drops more likely; the amounts change in every table.
+#[derive(Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Luck {
+    pub nebula: i32,
+    pub supernova: i32,
+    pub black_hole: i32,
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct LootEntry {
+    pub item: LootKind,
+    pub weight: u32,
+    // Inclusive [min, max] range, only for items that carry an amount
+    #[serde(default)]
+    pub amount: Option<(u32, u32)>,
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum LootKind {
+    Nothing,
+    Scrap,
+    RareMetal,
+    HealthPack,
+    RapidFireBoost,
+    BigBulletBoost,
+    Shield,
+}
+
+impl LootKind {
+    fn has_amount(self) -> bool {
+        matches!(
+            self,
+            LootKind::Scrap | LootKind::RareMetal | LootKind::HealthPack | LootKind::Shield
+        )
+    }
+}
+
+impl Luck {
+    pub fn get(&self, difficulty: Difficulty) -> i32 {
+        match difficulty {
+            Difficulty::Nebula => self.nebula,
+            Difficulty::Supernova => self.supernova,
+            Difficulty::BlackHole => self.black_hole,
+        }
+    }
+}
+
+impl LootEntry {
+    // Weight after the luck adjustment, in hundredths to stay in integers
+    fn weight_with_luck(&self, luck: i32) -> u32 {
+        if self.item == LootKind::Nothing {
+            self.weight * 100
+        } else {
+            self.weight * (100 + luck).max(0) as u32
+        }
+    }
+}
+
+impl LootTables {
+    pub fn table(&self, source: LootSource) -> &[LootEntry] {
+        match source {
+            LootSource::Asteroid => &self.asteroid,
+            LootSource::RareAsteroid => &self.rare_asteroid,
+            LootSource::EnemySmall => &self.enemy,
+        }
+    }
+
+    // Chance of each entry of a source's table, in table order, after luck is applied
+    pub fn chances(&self, source: LootSource, difficulty: Difficulty) -> Vec<(LootKind, f32)> {
+        let luck = self.luck.get(difficulty);
+        let table = self.table(source);
+        let total: u32 = table.iter().map(|e| e.weight_with_luck(luck)).sum();
+        table
+            .iter()
+            .map(|e| (e.item, e.weight_with_luck(luck) as f32 / total as f32))
+            .collect()
+    }
+
+    // Picks an item from the source's table, None when nothing drops
+    pub fn roll(
+        &self,
+        source: LootSource,
+        difficulty: Difficulty,
+        rng: &RandGenerator,
+    ) -> Option<LootType> {
+        let luck = self.luck.get(difficulty);
+        let table = self.table(source);
+        let total: u32 = table.iter().map(|e| e.weight_with_luck(luck)).sum();
+
+        let mut roll = rng.gen_range(0, total);
+        let entry = table.iter().find(|e| {
+            let weight = e.weight_with_luck(luck);
+            if roll < weight {
+                true
+            } else {
+                roll -= weight;
+                false
+            }
+        })?;
+
+        let amount = entry.amount.map_or(0, |(min, max)| {
+            amount_with_luck(rng.gen_range(min, max + 1), luck, rng)
+        });
+        match entry.item {
+            LootKind::Nothing => None,
+            LootKind::Scrap => Some(LootType::Scrap(amount)),
+            LootKind::RareMetal => Some(LootType::RareMetal(amount)),
+            LootKind::HealthPack => Some(LootType::HealthPack(amount as i32)),
+            LootKind::RapidFireBoost => Some(LootType::RapidFireBoost),
+            LootKind::BigBulletBoost => Some(LootType::BigBulletBoost),
+            LootKind::Shield => Some(LootType::Shield(amount)),
+        }
+    }
+}
+
+impl Validate for LootTables {
+    fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        for (name, luck) in [
+            ("nebula", self.luck.nebula),
+            ("supernova", self.luck.supernova),
+            ("black_hole", self.luck.black_hole),
+        ] {
+            if luck <= -100 {
+                problems.push(format!(
+                    "luck.{name}: must be greater than -100, got {luck}"
+                ));
+            }
+        }
+
+        for (name, table) in [
+            ("asteroid", &self.asteroid),
+            ("rare_asteroid", &self.rare_asteroid),
+            ("enemy", &self.enemy),
+        ] {
+            if table.iter().all(|entry| entry.weight == 0) {
+                problems.push(format!("{name}: needs at least one entry with a weight"));
+            }
+            for (i, entry) in table.iter().enumerate() {
+                let entry_name = format!("{name}[{i}] ({:?})", entry.item);
+                match entry.amount {
+                    Some(_) if !entry.item.has_amount() => {
+                        problems.push(format!("{entry_name}: this item has no `amount`"));
+                    }
+                    None if entry.item.has_amount() => {
+                        problems.push(format!("{entry_name}: `amount` is missing"));
+                    }
+                    Some((min, max)) if min > max => {
+                        problems.push(format!(
+                            "{entry_name}: `amount` range [{min}, {max}] is empty"
+                        ));
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        problems
+    }
+}
+
+// Scales a rolled amount by the luck. The fraction left over is rounded up with a chance
+// equal to its size, so the average amount changes by exactly the luck percentage even
+// for small amounts. A drop always carries at least 1.
+fn amount_with_luck(amount: u32, luck: i32, rng: &RandGenerator) -> u32 {
+    let hundredths = amount * (100 + luck).max(0) as u32;
+    let fraction = hundredths % 100;
+    let round_up = fraction > 0 && rng.gen_range(0, 100) < fraction;
+    (hundredths / 100 + round_up as u32).max(1)
+}
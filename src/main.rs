@@ -1,16 +1,184 @@
-mod components;
-mod draw;
-mod game;
-mod localization;
-mod resources;
-mod systems;
-
 use macroquad::prelude::*;
 
-use components::{GameState, MenuItem};
-use draw::draw_background;
-use game::*;
-use resources::Resources;
+use rust_in_space::components::{
+    CampaignSave, GameState, MenuItem, Notices, RebindCursor, SaveData, ScoreEntry,
+    DEFAULT_PILOT_NAME, PILOT_NAME_MAX_LEN, REBIND_TIMEOUT,
+};
+use rust_in_space::data::{GameData, DATA_DIR, TUNING_FILE};
+use rust_in_space::draw::draw_background;
+use rust_in_space::game::*;
+use rust_in_space::gamepad::Gamepads;
+use rust_in_space::input::{Action, Bindings, Key, BINDING_SLOTS};
+use rust_in_space::replay::{Replay, ReplayPlayer, ReplayRecorder, ReplayStep, LAST_RUN_FILE};
+use rust_in_space::resources::Resources;
+use rust_in_space::settings::Settings;
+use rust_in_space::storage::{save_dir, save_path};
+use rust_in_space::systems::{
+    clear_campaign, load_campaign, load_score, random_seed, save_campaign, save_score, today,
+};
+use rust_in_space::tuning::TuningWatcher;
+use rust_in_space::upgrades::Upgrade;
+use std::path::Path;
+
+// Returns the value following a command-line flag, e.g. `--seed 42`
+fn arg_value(flag: &str) -> Option<String> {
+    let args: Vec<String> = std::env::args().collect();
+    args.iter()
+        .position(|a| a == flag)
+        .and_then(|i| args.get(i + 1))
+        .cloned()
+}
+
+// The playfield is sized from the window when a mission starts and then stays
+// fixed, so a recorded mission always replays on the same arena
+fn screen_arena() -> Vec2 {
+    vec2(screen_width(), screen_height())
+}
+
+// Kept with the save files, so it does not depend on where the game was started
+fn save_replay(replay: &Replay, notices: &mut Notices) {
+    if let Err(e) = replay.save(&save_path(LAST_RUN_FILE)) {
+        warn!("Failed to save replay: {}", e);
+        notices.push("notice_save_replay_failed", e);
+    }
+}
+
+// Saves the campaign as it stands on the briefing screen, for Continue
+fn save_progress(game: &Game, notices: &mut Notices) -> CampaignSave {
+    let save = game.campaign_save();
+    if let Err(e) = save_campaign(&save) {
+        error!("Failed to save campaign: {}", e);
+        notices.push("notice_save_campaign_failed", e);
+    }
+    save
+}
+
+// Handles the rebinding screen's keys. Returns true when a binding changed.
+fn update_rebinding(cursor: &mut RebindCursor, controls: &mut Bindings, pads: &Gamepads) -> bool {
+    let pressed = |action| controls.is_pressed(action) || pads.is_pressed(action);
+    let rows = Action::ALL.len() + 1; // Plus "reset to defaults"
+
+    if cursor.waiting {
+        // Every bindable key, Escape included, is taken as the new binding. Waiting
+        // is cancelled with a mouse click or the gamepad, or ends by itself.
+        if let Some(key) = Key::pressed() {
+            cursor.waiting = false;
+            controls.bind(Action::ALL[cursor.row], cursor.slot, key);
+            return true;
+        }
+        if get_time() >= cursor.wait_until
+            || is_mouse_button_pressed(MouseButton::Left)
+            || is_mouse_button_pressed(MouseButton::Right)
+            || pads.is_pressed(Action::Pause)
+        {
+            cursor.waiting = false;
+        }
+        return false;
+    }
+
+    if pressed(Action::NavigateUp) {
+        cursor.row = (cursor.row + rows - 1) % rows;
+    }
+    if pressed(Action::NavigateDown) {
+        cursor.row = (cursor.row + 1) % rows;
+    }
+    if pressed(Action::NavigateLeft) || pressed(Action::NavigateRight) {
+        cursor.slot = (cursor.slot + 1) % BINDING_SLOTS;
+    }
+
+    let Some(&action) = Action::ALL.get(cursor.row) else {
+        // The reset row
+        if pressed(Action::Confirm) {
+            *controls = Bindings::default();
+            return true;
+        }
+        return false;
+    };
+    if pressed(Action::Confirm) {
+        cursor.waiting = true;
+        cursor.wait_until = get_time() + REBIND_TIMEOUT;
+    } else if pressed(Action::Erase) {
+        return controls.clear(action, cursor.slot);
+    }
+    false
+}
+
+// Whether an action was triggered this frame by its keys or a gamepad button
+fn pressed(settings: &Settings, pads: &Gamepads, action: Action) -> bool {
+    settings.controls.is_pressed(action) || pads.is_pressed(action)
+}
+
+fn save_settings(settings: &Settings, notices: &mut Notices) {
+    if let Err(e) = settings.save() {
+        error!("Failed to save settings: {}", e);
+        notices.push("notice_save_settings_failed", e);
+    }
+}
+
+// Applies this frame's typing to a leaderboard name. Only characters the pixel
+// font can draw are accepted; `erase` deletes the last one.
+fn read_name_input(name: &mut String, erase: bool, res: &Resources) {
+    // The queue hands out the most recent character first
+    let mut typed = Vec::new();
+    while let Some(c) = get_char_pressed() {
+        typed.push(c);
+    }
+    for c in typed.into_iter().rev() {
+        if !c.is_control()
+            && name.chars().count() < PILOT_NAME_MAX_LEN
+            && (c == ' ' || res.fonts.has_primary_glyph(res.lang.current_lang, c))
+        {
+            name.push(c);
+        }
+    }
+    if erase {
+        name.pop();
+    }
+}
+
+// Letters a gamepad steps through on the name entry screen
+const PAD_LETTERS: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789 ";
+
+// Spells a leaderboard name with the gamepad: up and down change the last letter,
+// right adds another one
+fn pick_name_letter(name: &mut String, pads: &Gamepads) {
+    let step = if pads.is_pressed(Action::NavigateUp) {
+        1
+    } else if pads.is_pressed(Action::NavigateDown) {
+        PAD_LETTERS.len() - 1
+    } else {
+        0
+    };
+    let room = name.chars().count() < PILOT_NAME_MAX_LEN;
+    if pads.is_pressed(Action::NavigateRight) && room {
+        name.push('A');
+    } else if step != 0 {
+        // Letters the pad cannot reach start over from A
+        let index = name
+            .pop()
+            .and_then(|last| PAD_LETTERS.find(last))
+            .map_or(0, |i| (i + step) % PAD_LETTERS.len());
+        name.extend(PAD_LETTERS[index..].chars().next());
+    }
+}
+
+const TUNING_POLL_INTERVAL: f64 = 0.5; // Seconds between checks of tuning.json
+
+// Applies edits to tuning.json while the game runs. A broken file keeps the old values.
+// A replay being watched keeps its recorded values; the edit applies once it stops.
+fn reload_tuning(watcher: &mut TuningWatcher, game: &mut Game, replay: Option<&mut ReplayPlayer>) {
+    match watcher.poll() {
+        Some(Ok(tuning)) => {
+            match replay {
+                Some(player) => player.set_live_tuning(tuning),
+                None => game.set_tuning(tuning),
+            }
+            info!("Reloaded {}", TUNING_FILE);
+        }
+        Some(Err(e)) => warn!("Keeping previous tuning values: {}", e),
+        None => {}
+    }
+}
 
 fn window_conf() -> Conf {
     Conf {
@@ -25,110 +193,405 @@ fn window_conf() -> Conf {
 #[macroquad::main(window_conf)]
 async fn main() {
     let mut state = GameState::Menu;
-    let mut resources = Resources::new().await;
-    let mut game = Game::new();
+    // `--seed <number>` replays a run with the same spawns and loot
+    let fixed_seed = arg_value("--seed").and_then(|s| s.parse().ok());
+    let mut clock = FixedTimestep::new();
+    let data = match GameData::load(Path::new(DATA_DIR)) {
+        Ok(data) => data,
+        Err(e) => {
+            error!("Failed to load game data: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let mut notices = Notices::default();
+    let mut settings = Settings::load().unwrap_or_else(|e| {
+        error!("Failed to load settings: {}", e);
+        notices.push("notice_load_settings_failed", e);
+        Settings::default()
+    });
+    settings.apply_window_mode();
+    let mut resources = match Resources::new(&data, &settings).await {
+        Ok(resources) => resources,
+        Err(e) => {
+            error!("Failed to load game assets: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let mut game = Game::new(screen_arena(), fixed_seed.unwrap_or_else(random_seed), data);
+    game.difficulty = settings.difficulty;
+
+    let mut tuning_watcher = TuningWatcher::new(Path::new(DATA_DIR).join(TUNING_FILE));
+    let mut next_tuning_check = 0.0;
+
+    let mut pads = Gamepads::new();
+    let mut rebind = RebindCursor::default();
+    let mut hangar_row = 0; // Selected row of the Hangar screen
+    let mut pilot_name = String::new(); // Kept between runs, so the next entry starts with it
+    info!("Save files are kept in {}", save_dir().display());
+    let mut save_data = load_score(settings.difficulty).unwrap_or_else(|e| {
+        error!("Failed to load high scores: {}", e);
+        notices.push("notice_load_scores_failed", e);
+        SaveData::default()
+    });
+    // Last saved briefing, offered as Continue
+    let mut campaign = load_campaign().unwrap_or_else(|e| {
+        error!("Failed to load campaign: {}", e);
+        notices.push("notice_load_campaign_failed", e);
+        None
+    });
+    if campaign.is_some() {
+        game.menu_selection = MenuItem::Continue;
+    }
+
+    let mut recorder = ReplayRecorder::new(game.seed, game.difficulty);
+    let mut replay: Option<ReplayPlayer> = None; // Set while a replay is being watched
+    let mut last_replay: Option<Replay> = None; // Offered on the game over screen
+
+    // `--replay <file>` starts watching a recorded run right away
+    if let Some(path) = arg_value("--replay") {
+        match Replay::load(Path::new(&path)) {
+            Ok(loaded) => {
+                last_replay = Some(loaded.clone());
+                replay = Some(ReplayPlayer::start(loaded, &mut game));
+                state = GameState::Replay;
+            }
+            Err(e) => {
+                error!("{}", e);
+                notices.push("notice_load_replay_failed", e);
+            }
+        }
+    }
 
     loop {
+        if get_time() >= next_tuning_check {
+            next_tuning_check = get_time() + TUNING_POLL_INTERVAL;
+            reload_tuning(&mut tuning_watcher, &mut game, replay.as_mut());
+        }
+        pads.update();
+
         clear_background(BLACK);
         draw_background(&resources.background);
 
         match state {
             GameState::Menu => {
-                render_menu(&game, &resources);
+                let can_continue = campaign.is_some();
+                if !can_continue && game.menu_selection == MenuItem::Continue {
+                    game.menu_selection = MenuItem::Start;
+                }
+                render_menu(&game, can_continue, &settings, &resources);
 
                 // Menu navigation
-                if is_key_pressed(KeyCode::Up) {
-                    game.menu_selection = game.menu_selection.prev();
+                if pressed(&settings, &pads, Action::NavigateUp) {
+                    game.menu_selection = game.menu_selection.prev(can_continue);
                 }
-                if is_key_pressed(KeyCode::Down) {
-                    game.menu_selection = game.menu_selection.next();
+                if pressed(&settings, &pads, Action::NavigateDown) {
+                    game.menu_selection = game.menu_selection.next(can_continue);
                 }
 
                 // Handle actions based on selected menu item
                 match game.menu_selection {
+                    MenuItem::Continue => {
+                        if let Some(save) = campaign
+                            .as_ref()
+                            .filter(|_| pressed(&settings, &pads, Action::Confirm))
+                        {
+                            game.arena = screen_arena();
+                            game.resume_campaign(save);
+                            recorder = ReplayRecorder::resumed(save);
+                            state = GameState::Briefing;
+                        }
+                    }
                     MenuItem::Start => {
-                        if is_key_pressed(KeyCode::Enter) {
-                            game.reset();
+                        if pressed(&settings, &pads, Action::Confirm) {
+                            game.arena = screen_arena();
+                            game.reset(fixed_seed.unwrap_or_else(random_seed));
+                            recorder = ReplayRecorder::new(game.seed, game.difficulty);
+                            campaign = Some(save_progress(&game, &mut notices));
                             state = GameState::Briefing;
                         }
                     }
+                    MenuItem::HighScores => {
+                        if pressed(&settings, &pads, Action::Confirm) {
+                            state = GameState::HighScores(game.difficulty);
+                        }
+                    }
                     MenuItem::Difficulty => {
-                        if is_key_pressed(KeyCode::Left) || is_key_pressed(KeyCode::Right) {
+                        if pressed(&settings, &pads, Action::NavigateLeft)
+                            || pressed(&settings, &pads, Action::NavigateRight)
+                        {
                             game.cycle_difficulty();
+                            settings.difficulty = game.difficulty;
+                            save_settings(&settings, &mut notices);
                         }
                     }
                     MenuItem::Language => {
-                        if is_key_pressed(KeyCode::Left) || is_key_pressed(KeyCode::Right) {
+                        if pressed(&settings, &pads, Action::NavigateLeft)
+                            || pressed(&settings, &pads, Action::NavigateRight)
+                        {
                             resources.lang.cycle_lang();
+                            settings.language = resources.lang.current_lang;
+                            save_settings(&settings, &mut notices);
+                        }
+                    }
+                    MenuItem::Controls => {
+                        if pressed(&settings, &pads, Action::Confirm) {
+                            rebind = RebindCursor::default();
+                            state = GameState::Controls;
+                        }
+                    }
+                    MenuItem::ControlScheme => {
+                        if pressed(&settings, &pads, Action::NavigateLeft)
+                            || pressed(&settings, &pads, Action::NavigateRight)
+                        {
+                            settings.control_scheme = settings.control_scheme.next();
+                            save_settings(&settings, &mut notices);
+                        }
+                    }
+                    MenuItem::WindowMode => {
+                        if pressed(&settings, &pads, Action::NavigateLeft)
+                            || pressed(&settings, &pads, Action::NavigateRight)
+                        {
+                            settings.window_mode = settings.window_mode.toggle();
+                            settings.apply_window_mode();
+                            save_settings(&settings, &mut notices);
                         }
                     }
                 }
             }
 
             GameState::Briefing => {
-                render_briefing(&game.current_mission, &resources);
+                render_briefing(&game.current_mission, &settings.controls, &resources);
 
-                if is_key_pressed(KeyCode::Space) {
+                if pressed(&settings, &pads, Action::Fire) {
+                    game.arena = screen_arena();
                     game.start_mission();
+                    recorder.begin_mission(&game);
+                    clock.reset();
                     state = GameState::Playing;
                 }
             }
 
             GameState::Playing => {
+                let input = read_player_input(&settings, &pads, &game.ship, game.arena);
+
                 // Check for pause
-                if is_key_pressed(KeyCode::Escape) {
+                if input.pause {
+                    recorder.record_pause();
+                    save_replay(&recorder.replay, &mut notices);
                     state = GameState::Paused;
                 } else {
-                    let dt = get_frame_time();
-
-                    if game.is_mission_complete() {
-                        state = GameState::MissionSuccess;
+                    // Advance the simulation in fixed steps regardless of the frame rate
+                    for _ in 0..clock.advance(get_frame_time()) {
+                        if game.is_mission_complete() {
+                            save_replay(&recorder.replay, &mut notices);
+                            state = GameState::MissionSuccess;
+                            break;
+                        }
+                        recorder.record(&input, &game);
+                        if update_game(&mut game, &input, TICK_DT) {
+                            if let Err(e) = clear_campaign() {
+                                error!("Failed to delete campaign save: {}", e);
+                                notices.push("notice_delete_campaign_failed", e);
+                            }
+                            campaign = None;
+                            save_replay(&recorder.replay, &mut notices);
+                            last_replay = Some(recorder.replay.clone());
+                            state = if save_data.qualifies(game.difficulty, game.score) {
+                                clear_input_queue(); // Drop letters typed while playing
+                                GameState::NameEntry(game.score)
+                            } else {
+                                GameState::GameOver(game.score)
+                            };
+                            break;
+                        }
                     }
 
-                    update_timers(&mut game, dt);
-                    update_ship_movement(&mut game, dt);
-                    update_ship_shooting(&mut game);
-                    update_enemies(&mut game, dt);
-                    update_loot(&mut game, dt);
-                    update_physics(&mut game, dt);
+                    render_game(&game, &resources, clock.alpha());
+                }
+            }
+
+            GameState::Replay => {
+                let Some(player) = replay.as_mut() else {
+                    state = GameState::Menu;
+                    continue;
+                };
 
-                    if update_collisions(&mut game) {
-                        state = GameState::GameOver(game.score);
+                if pressed(&settings, &pads, Action::Pause) {
+                    state = GameState::Paused;
+                } else if pressed(&settings, &pads, Action::Confirm) {
+                    if let Some(player) = replay.take() {
+                        player.stop(&mut game);
+                    }
+                    state = GameState::Menu;
+                } else {
+                    for _ in 0..clock.advance(get_frame_time()) {
+                        match player.step(&mut game) {
+                            ReplayStep::Running => {}
+                            ReplayStep::Paused => {
+                                state = GameState::Paused;
+                                break;
+                            }
+                            ReplayStep::GameOver | ReplayStep::Finished => {
+                                if let Some(player) = replay.take() {
+                                    player.stop(&mut game);
+                                }
+                                state = GameState::GameOver(game.score);
+                                break;
+                            }
+                        }
                     }
 
-                    render_game(&game, &resources);
+                    render_game(&game, &resources, clock.alpha());
+                    render_replay_overlay(&settings.controls.key_name(Action::Confirm), &resources);
                 }
             }
 
             GameState::Paused => {
                 // Render the game in paused state (frozen frame)
-                render_game(&game, &resources);
-                render_pause(&resources);
+                render_game(&game, &resources, clock.alpha());
+                render_pause(&settings.controls.key_name(Action::Pause), &resources);
 
                 // Check for unpause
-                if is_key_pressed(KeyCode::Escape) {
-                    state = GameState::Playing;
+                if pressed(&settings, &pads, Action::Pause) {
+                    clock.reset();
+                    state = if replay.is_some() {
+                        GameState::Replay
+                    } else {
+                        GameState::Playing
+                    };
                 }
             }
 
             GameState::MissionSuccess => {
-                render_mission_success(&game.current_mission, &resources);
+                render_mission_success(
+                    &game.current_mission,
+                    &settings.controls.key_name(Action::Confirm),
+                    &resources,
+                );
 
-                if is_key_pressed(KeyCode::Enter) {
+                if pressed(&settings, &pads, Action::Confirm) {
                     game.next_mission();
+                    campaign = Some(save_progress(&game, &mut notices));
+                    hangar_row = 0;
+                    state = GameState::Hangar;
+                }
+            }
+
+            GameState::Hangar => {
+                render_hangar(&game, hangar_row, &settings.controls, &resources);
+
+                let rows = Upgrade::ALL.len() + 1; // Plus "continue to the briefing"
+                if pressed(&settings, &pads, Action::NavigateUp) {
+                    hangar_row = (hangar_row + rows - 1) % rows;
+                }
+                if pressed(&settings, &pads, Action::NavigateDown) {
+                    hangar_row = (hangar_row + 1) % rows;
+                }
+                if pressed(&settings, &pads, Action::Confirm) {
+                    match Upgrade::ALL.get(hangar_row) {
+                        Some(&upgrade) => {
+                            // Every purchase is saved right away
+                            if game.buy_upgrade(upgrade) {
+                                campaign = Some(save_progress(&game, &mut notices));
+                            }
+                        }
+                        None => state = GameState::Briefing,
+                    }
+                } else if pressed(&settings, &pads, Action::Pause) {
                     state = GameState::Briefing;
                 }
             }
 
+            GameState::NameEntry(score) => {
+                read_name_input(
+                    &mut pilot_name,
+                    pressed(&settings, &pads, Action::Erase),
+                    &resources,
+                );
+                pick_name_letter(&mut pilot_name, &pads);
+                render_name_entry(score, &pilot_name, &settings.controls, &resources);
+
+                // Enter always works here, in case Confirm is bound to a letter the
+                // pilot wants to type
+                if is_key_pressed(KeyCode::Enter)
+                    || is_key_pressed(KeyCode::KpEnter)
+                    || pressed(&settings, &pads, Action::Confirm)
+                {
+                    let name = pilot_name.trim();
+                    let entry = ScoreEntry {
+                        name: if name.is_empty() {
+                            DEFAULT_PILOT_NAME.to_string()
+                        } else {
+                            name.to_string()
+                        },
+                        score,
+                        level: game.current_level_idx,
+                        date: today(),
+                    };
+                    save_data.add(game.difficulty, entry);
+                    if let Err(e) = save_score(&save_data) {
+                        error!("Failed to save high scores: {}", e);
+                        notices.push("notice_save_scores_failed", e);
+                    }
+                    state = GameState::GameOver(score);
+                }
+            }
+
+            GameState::Controls => {
+                render_controls(&settings.controls, &rebind, &resources);
+                let was_waiting = rebind.waiting; // Escape then only cancels the wait
+                if update_rebinding(&mut rebind, &mut settings.controls, &pads) {
+                    save_settings(&settings, &mut notices);
+                }
+                if !was_waiting && pressed(&settings, &pads, Action::Pause) {
+                    state = GameState::Menu;
+                }
+            }
+
+            GameState::HighScores(difficulty) => {
+                render_high_scores(
+                    save_data.board(difficulty),
+                    difficulty,
+                    &settings.controls,
+                    &resources,
+                );
+
+                if pressed(&settings, &pads, Action::NavigateLeft) {
+                    state = GameState::HighScores(difficulty.prev());
+                } else if pressed(&settings, &pads, Action::NavigateRight) {
+                    state = GameState::HighScores(difficulty.next());
+                } else if pressed(&settings, &pads, Action::Confirm)
+                    || pressed(&settings, &pads, Action::Pause)
+                {
+                    state = GameState::Menu;
+                }
+            }
+
             GameState::GameOver(score) => {
-                render_game_over(score, &resources);
+                render_game_over(
+                    score,
+                    save_data.high_score(game.difficulty),
+                    game.seed,
+                    last_replay.is_some(),
+                    &settings.controls,
+                    &resources,
+                );
 
-                if is_key_pressed(KeyCode::Enter) {
+                if pressed(&settings, &pads, Action::Confirm) {
+                    // A continued campaign played on its own difficulty
+                    game.difficulty = settings.difficulty;
                     state = GameState::Menu;
+                } else if pressed(&settings, &pads, Action::Replay) {
+                    if let Some(recorded) = &last_replay {
+                        replay = Some(ReplayPlayer::start(recorded.clone(), &mut game));
+                        clock.reset();
+                        state = GameState::Replay;
+                    }
                 }
             }
         }
 
+        render_notices(&mut notices, &resources);
         next_frame().await
     }
 }
@@ -0,0 +1,237 @@
+use crate::components::Mission;
+use crate::data::Validate;
+use crate::enemies::{EnemyMixEntry, EnemySet};
+use crate::localization::Localization;
+use serde::Deserialize;
+
+// More large asteroids than this fill the screen; generated levels stop adding them here
+pub const MAX_ASTEROID_COUNT: usize = 40;
+
+// Mission definitions from missions.json. The scripted missions are played in
+// order; after the last one, levels are generated from the `endless` formulas.
+#[derive(Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct MissionSet {
+    pub missions: Vec<MissionDef>,
+    pub endless: EndlessDef,
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct MissionDef {
+    pub title_key: String, // Localization keys
+    pub description_key: String,
+    pub target_kills: u32,
+    pub target_scrap: u32,
+    pub target_rare_metal: u32,
+    pub enemy_spawn_interval: f32,
+    pub asteroid_count: usize,
+    pub enemies: Vec<EnemyMixEntry>,
+}
+
+// Formulas for the generated levels
+#[derive(Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct EndlessDef {
+    pub title_key: String,
+    pub description_key: String,
+    pub target_kills: LevelScaling,
+    pub target_scrap: LevelScaling,
+    pub target_rare_metal: LevelScaling,
+    pub enemy_spawn_interval: IntervalScaling,
+    pub asteroid_count: LevelScaling,
+    pub enemies: Vec<EnemyMixEntry>,
+}
+
+// base + level / every
+#[derive(Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct LevelScaling {
+    pub base: u32,
+    pub every: u32,
+}
+
+// max(start - level * step, min)
+#[derive(Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct IntervalScaling {
+    pub start: f32,
+    pub step: f32,
+    pub min: f32,
+}
+
+impl LevelScaling {
+    fn at(&self, level: u32) -> u32 {
+        self.base + level / self.every
+    }
+}
+
+impl IntervalScaling {
+    fn at(&self, level: u32) -> f32 {
+        (self.start - level as f32 * self.step).max(self.min)
+    }
+}
+
+impl MissionSet {
+    // Checks that every enemy mix only names archetypes from enemies.json
+    pub fn validate_enemies(&self, enemies: &EnemySet) -> Vec<String> {
+        let mixes = self
+            .missions
+            .iter()
+            .enumerate()
+            .map(|(i, m)| (format!("missions[{i}]"), &m.enemies))
+            .chain([("endless".to_string(), &self.endless.enemies)]);
+
+        let mut problems = Vec::new();
+        for (name, mix) in mixes {
+            if mix.iter().all(|entry| entry.weight == 0) {
+                problems.push(format!(
+                    "{name}: `enemies` needs at least one entry with a weight"
+                ));
+            }
+            for entry in mix {
+                if enemies.find(&entry.archetype).is_none() {
+                    problems.push(format!(
+                        "{name}: unknown enemy archetype \"{}\"",
+                        entry.archetype
+                    ));
+                }
+            }
+        }
+        problems
+    }
+
+    // Mission for a level, counting from 1
+    pub fn get(&self, level: u32) -> Mission {
+        if let Some(def) = (level as usize)
+            .checked_sub(1)
+            .and_then(|i| self.missions.get(i))
+        {
+            return Mission {
+                level_id: level,
+                title_key: def.title_key.clone(),
+                description_key: def.description_key.clone(),
+                target_kills: def.target_kills,
+                target_scrap: def.target_scrap,
+                target_rare_metal: def.target_rare_metal,
+                enemy_spawn_interval: def.enemy_spawn_interval,
+                asteroid_count: def.asteroid_count,
+                enemy_mix: def.enemies.clone(),
+            };
+        }
+
+        // generate infinite levels after the scripted ones
+        let e = &self.endless;
+        Mission {
+            level_id: level,
+            title_key: e.title_key.clone(),
+            description_key: e.description_key.clone(),
+            target_kills: e.target_kills.at(level),
+            target_scrap: e.target_scrap.at(level),
+            target_rare_metal: e.target_rare_metal.at(level),
+            enemy_spawn_interval: e.enemy_spawn_interval.at(level),
+            asteroid_count: (e.asteroid_count.at(level) as usize).min(MAX_ASTEROID_COUNT),
+            enemy_mix: e.enemies.clone(),
+        }
+    }
+}
+
+impl Validate for MissionSet {
+    fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        if self.missions.is_empty() {
+            problems.push("`missions` must contain at least one mission".to_string());
+        }
+        for (i, m) in self.missions.iter().enumerate() {
+            let name = format!("missions[{i}] (level {}, \"{}\")", i + 1, m.title_key);
+            if m.title_key.trim().is_empty() {
+                problems.push(format!("{name}: `title_key` is empty"));
+            }
+            if m.description_key.trim().is_empty() {
+                problems.push(format!("{name}: `description_key` is empty"));
+            }
+            if m.enemy_spawn_interval <= 0.0 {
+                problems.push(format!(
+                    "{name}: `enemy_spawn_interval` must be greater than 0, got {}",
+                    m.enemy_spawn_interval
+                ));
+            }
+            if m.target_kills == 0 && m.target_scrap == 0 && m.target_rare_metal == 0 {
+                problems.push(format!(
+                    "{name}: needs at least one objective (kills, scrap or rare metal)"
+                ));
+            }
+            if m.asteroid_count == 0 || m.asteroid_count > MAX_ASTEROID_COUNT {
+                problems.push(format!(
+                    "{name}: `asteroid_count` must be between 1 and {MAX_ASTEROID_COUNT}, got {}",
+                    m.asteroid_count
+                ));
+            }
+        }
+
+        let e = &self.endless;
+        if e.title_key.trim().is_empty() {
+            problems.push("endless: `title_key` is empty".to_string());
+        }
+        if e.description_key.trim().is_empty() {
+            problems.push("endless: `description_key` is empty".to_string());
+        }
+        // Otherwise the first generated levels are complete as soon as they start
+        if e.target_kills.base == 0 && e.target_scrap.base == 0 && e.target_rare_metal.base == 0 {
+            problems.push(
+                "endless: needs at least one objective with a `base` above 0 (kills, scrap or rare metal)"
+                    .to_string(),
+            );
+        }
+        for (field, scaling) in [
+            ("target_kills", &e.target_kills),
+            ("target_scrap", &e.target_scrap),
+            ("target_rare_metal", &e.target_rare_metal),
+            ("asteroid_count", &e.asteroid_count),
+        ] {
+            if scaling.every == 0 {
+                problems.push(format!("endless.{field}: `every` must be at least 1"));
+            }
+        }
+        let base = e.asteroid_count.base as usize;
+        if base == 0 || base > MAX_ASTEROID_COUNT {
+            problems.push(format!(
+                "endless.asteroid_count: `base` must be between 1 and {MAX_ASTEROID_COUNT}, got {base}"
+            ));
+        }
+        if e.enemy_spawn_interval.step < 0.0 {
+            problems.push(format!(
+                "endless.enemy_spawn_interval: `step` must not be negative, got {}",
+                e.enemy_spawn_interval.step
+            ));
+        }
+        if e.enemy_spawn_interval.min <= 0.0 {
+            problems.push(format!(
+                "endless.enemy_spawn_interval: `min` must be greater than 0, got {}",
+                e.enemy_spawn_interval.min
+            ));
+        }
+        if e.enemy_spawn_interval.start < e.enemy_spawn_interval.min {
+            problems
+                .push("endless.enemy_spawn_interval: `start` must not be below `min`".to_string());
+        }
+
+        problems
+    }
+}
+
+// Localized mission title or description. The texts can use `{level}`, `{kills}`,
+// `{scrap}` and `{gold}`, so generated levels and changed targets read correctly;
+// `{kills:key}` and the like pick the plural form of `key` for the target.
+pub fn mission_text(mission: &Mission, key: &str, lang: &Localization) -> String {
+    lang.format(
+        key,
+        &[
+            ("level", mission.level_id.into()),
+            ("kills", mission.target_kills.into()),
+            ("scrap", mission.target_scrap.into()),
+            ("gold", mission.target_rare_metal.into()),
+        ],
+    )
+}
@@ -0,0 +1,400 @@
+use crate::components::{CampaignSave, Difficulty, PlayerInput};
+use crate::game::{update_game, Game, TICK_DT};
+use crate::storage::{self, StorageError};
+use crate::tuning::Tuning;
+use crate::upgrades::Upgrades;
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+pub const REPLAY_VERSION: u32 = 4;
+pub const LAST_RUN_FILE: &str = "replays/last_run.json";
+
+// Input stored per tick, packed into a u64: button bits in the low byte, then
+// the turn as a signed byte (-127..=127), the thrust and the reverse thrust as
+// bytes (0..=255).
+// The upper half holds the mouse aim and thrust headings as fractions of a turn,
+// each present when its flag is set. Version 2 stored the lower half only.
+const FIRE: u64 = 1;
+const PAUSE: u64 = 1 << 1;
+const AIM: u64 = 1 << 2;
+const THRUST_HEADING: u64 = 1 << 3;
+const BRAKE: u64 = 1 << 4;
+const TURN_SHIFT: u32 = 8;
+const THRUST_SHIFT: u32 = 16;
+const REVERSE_SHIFT: u32 = 24;
+const AIM_SHIFT: u32 = 32;
+const THRUST_HEADING_SHIFT: u32 = 48;
+const TURN_STEPS: f32 = 127.0;
+const THRUST_STEPS: f32 = 255.0;
+const HEADING_STEPS: f32 = 65536.0;
+
+// Version 1 stored only keys: rotate left, rotate right, thrust, fire, pause
+const V1_ROTATE_LEFT: u64 = 1;
+const V1_ROTATE_RIGHT: u64 = 1 << 1;
+const V1_THRUST: u64 = 1 << 2;
+const V1_FIRE: u64 = 1 << 3;
+const V1_PAUSE: u64 = 1 << 4;
+
+// Handling that tuning entries of replays before version 4 lack: the ship had no
+// reverse thrusters or brake yet and its top speed was fixed
+const V3_TUNING_DEFAULTS: [(&str, f32); 3] = [
+    ("reverse_acceleration", 0.0),
+    ("brake_deceleration", 0.0),
+    ("max_speed", 420.0),
+];
+
+// Everything needed to re-run a game tick by tick: the seed and difficulty
+// fix all randomness, the input tracks fix everything the pilot did, and the
+// recorded tuning values fix how the ship handled while they did it.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Replay {
+    pub version: u32,
+    pub seed: u64,
+    pub difficulty: Difficulty,
+    // Set when the run continued a saved campaign instead of starting at level 1
+    #[serde(default)]
+    pub start: Option<CampaignSave>,
+    pub missions: Vec<MissionTrack>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct MissionTrack {
+    pub level: u32,
+    pub arena: (f32, f32),
+    pub inputs: Vec<(u64, u32)>, // Run-length encoded input: (packed input, tick count)
+    // What the ship carried into the mission, after any Hangar purchases.
+    // Missing in replays recorded before the Hangar existed.
+    #[serde(default)]
+    pub loadout: Option<Loadout>,
+    // Tuning in effect from the given tick of the mission on; a new entry for every
+    // reload of tuning.json. Empty in replays recorded before tuning was stored,
+    // which play with the current values.
+    #[serde(default)]
+    pub tuning: Vec<(u32, Tuning)>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Loadout {
+    pub upgrades: Upgrades,
+    pub scrap: u32,
+    pub rare_metal: u32,
+}
+
+// What happened while advancing a replay by one tick
+pub enum ReplayStep {
+    Running,
+    Paused,   // The pilot paused the game at this point of the run
+    GameOver, // The recorded run ended with the ship destroyed
+    Finished, // No more recorded input
+}
+
+impl PlayerInput {
+    // Rounds the analog values to the steps a replay stores. The live game
+    // plays with the rounded input, so its replay follows the same path.
+    pub fn quantized(self) -> Self {
+        Self::from_code(self.to_code())
+    }
+
+    fn to_code(self) -> u64 {
+        let mut code = 0;
+        if self.fire {
+            code |= FIRE;
+        }
+        if self.pause {
+            code |= PAUSE;
+        }
+        if self.brake {
+            code |= BRAKE;
+        }
+        if let Some(aim) = self.aim {
+            code |= AIM | (heading_to_steps(aim) as u64) << AIM_SHIFT;
+        }
+        if let Some(heading) = self.thrust_heading {
+            code |= THRUST_HEADING | (heading_to_steps(heading) as u64) << THRUST_HEADING_SHIFT;
+        }
+        let turn = (self.turn.clamp(-1.0, 1.0) * TURN_STEPS).round() as i8;
+        let thrust = (self.thrust.clamp(0.0, 1.0) * THRUST_STEPS).round() as u8;
+        let reverse = (self.reverse.clamp(0.0, 1.0) * THRUST_STEPS).round() as u8;
+        code | (turn as u8 as u64) << TURN_SHIFT
+            | (thrust as u64) << THRUST_SHIFT
+            | (reverse as u64) << REVERSE_SHIFT
+    }
+
+    fn from_code(code: u64) -> Self {
+        let turn = (code >> TURN_SHIFT) as u8 as i8;
+        let thrust = (code >> THRUST_SHIFT) as u8;
+        let reverse = (code >> REVERSE_SHIFT) as u8;
+        Self {
+            turn: turn as f32 / TURN_STEPS,
+            thrust: thrust as f32 / THRUST_STEPS,
+            reverse: reverse as f32 / THRUST_STEPS,
+            brake: code & BRAKE != 0,
+            fire: code & FIRE != 0,
+            pause: code & PAUSE != 0,
+            aim: (code & AIM != 0).then(|| steps_to_heading((code >> AIM_SHIFT) as u16)),
+            thrust_heading: (code & THRUST_HEADING != 0)
+                .then(|| steps_to_heading((code >> THRUST_HEADING_SHIFT) as u16)),
+        }
+    }
+
+    fn from_v1_bits(bits: u64) -> Self {
+        let mut turn = 0.0;
+        if bits & V1_ROTATE_LEFT != 0 {
+            turn -= 1.0;
+        }
+        if bits & V1_ROTATE_RIGHT != 0 {
+            turn += 1.0;
+        }
+        Self {
+            turn,
+            thrust: if bits & V1_THRUST != 0 { 1.0 } else { 0.0 },
+            fire: bits & V1_FIRE != 0,
+            pause: bits & V1_PAUSE != 0,
+            ..Default::default()
+        }
+    }
+}
+
+// A heading in degrees as a fraction of a full turn
+fn heading_to_steps(degrees: f32) -> u16 {
+    ((degrees.rem_euclid(360.0) / 360.0 * HEADING_STEPS).round() as u32 % 65536) as u16
+}
+
+fn steps_to_heading(steps: u16) -> f32 {
+    steps as f32 / HEADING_STEPS * 360.0
+}
+
+impl Replay {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("cannot read replay {}: {e}", path.display()))?;
+        Self::parse(&content).map_err(|e| format!("replay {}: {e}", path.display()))
+    }
+
+    // Reads a replay of this or any older version
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let mut value: serde_json::Value =
+            serde_json::from_str(content).map_err(|e| format!("invalid replay: {e}"))?;
+        let version = value["version"].as_u64().unwrap_or(0);
+        if version < 4 {
+            let tracks = value.get_mut("missions").and_then(|m| m.as_array_mut());
+            for track in tracks.into_iter().flatten() {
+                let entries = track.get_mut("tuning").and_then(|t| t.as_array_mut());
+                for entry in entries.into_iter().flatten() {
+                    let Some(tuning) = entry.get_mut(1).and_then(|t| t.as_object_mut()) else {
+                        continue;
+                    };
+                    for (field, default) in V3_TUNING_DEFAULTS {
+                        tuning.entry(field).or_insert(default.into());
+                    }
+                }
+            }
+        }
+        let mut replay: Replay =
+            serde_json::from_value(value).map_err(|e| format!("invalid replay: {e}"))?;
+        if replay.version == 1 {
+            // Same ticks, keyboard-only input
+            for track in &mut replay.missions {
+                for (code, _) in &mut track.inputs {
+                    *code = PlayerInput::from_v1_bits(*code).to_code();
+                }
+            }
+            replay.version = 2;
+        }
+        if replay.version == 2 || replay.version == 3 {
+            // Version 2 codes are version 3 codes without mouse aim, and version 3
+            // codes are version 4 codes without reverse thrust and brake
+            replay.version = REPLAY_VERSION;
+        }
+        if replay.version != REPLAY_VERSION {
+            return Err(format!(
+                "version {} is not supported, expected {REPLAY_VERSION}",
+                replay.version
+            ));
+        }
+        Ok(replay)
+    }
+
+    // Written atomically with a backup, like the other save files
+    pub fn save(&self, path: &Path) -> Result<(), StorageError> {
+        storage::write_json(path, self)
+    }
+}
+
+pub struct ReplayRecorder {
+    pub replay: Replay,
+    pause_pending: bool,
+    ticks: u32, // Recorded so far in the current mission
+}
+
+impl ReplayRecorder {
+    pub fn new(seed: u64, difficulty: Difficulty) -> Self {
+        Self {
+            replay: Replay {
+                version: REPLAY_VERSION,
+                seed,
+                difficulty,
+                start: None,
+                missions: Vec::new(),
+            },
+            pause_pending: false,
+            ticks: 0,
+        }
+    }
+
+    // For a run that continues a saved campaign
+    pub fn resumed(save: &CampaignSave) -> Self {
+        let mut recorder = Self::new(save.seed, save.difficulty);
+        recorder.replay.start = Some(save.clone());
+        recorder
+    }
+
+    // Call right after Game::start_mission
+    pub fn begin_mission(&mut self, game: &Game) {
+        self.replay.missions.push(MissionTrack {
+            level: game.current_level_idx,
+            arena: (game.arena.x, game.arena.y),
+            inputs: Vec::new(),
+            loadout: Some(Loadout {
+                upgrades: game.ship.upgrades.clone(),
+                scrap: game.ship.scrap,
+                rare_metal: game.ship.rare_metal,
+            }),
+            tuning: vec![(0, game.data.tuning.clone())],
+        });
+        self.ticks = 0;
+    }
+
+    // The pause is stored on the tick that follows it
+    pub fn record_pause(&mut self) {
+        self.pause_pending = true;
+    }
+
+    // Call before update_game with the input of the tick
+    pub fn record(&mut self, input: &PlayerInput, game: &Game) {
+        let mut input = *input;
+        input.pause = std::mem::take(&mut self.pause_pending);
+        let code = input.to_code();
+
+        let Some(track) = self.replay.missions.last_mut() else {
+            return;
+        };
+        match track.inputs.last_mut() {
+            Some((last_code, count)) if *last_code == code => *count += 1,
+            _ => track.inputs.push((code, 1)),
+        }
+        // tuning.json was reloaded since the last tick
+        if track.tuning.last().map(|(_, t)| t) != Some(&game.data.tuning) {
+            track.tuning.push((self.ticks, game.data.tuning.clone()));
+        }
+        self.ticks += 1;
+    }
+}
+
+pub struct ReplayPlayer {
+    pub replay: Replay,
+    mission_idx: usize,
+    run_idx: usize,  // Index into the current track's run-length pairs
+    run_offset: u32, // Ticks already consumed from the current run
+    tick: u32,       // Ticks played in the current mission
+    // The player's own tuning and difficulty, restored when the replay stops
+    live_tuning: Tuning,
+    live_difficulty: Difficulty,
+}
+
+impl ReplayPlayer {
+    // Resets the game to the recorded starting conditions. Call stop when leaving
+    // the replay.
+    pub fn start(replay: Replay, game: &mut Game) -> Self {
+        let live_tuning = game.data.tuning.clone();
+        let live_difficulty = game.difficulty;
+        match &replay.start {
+            Some(save) => game.resume_campaign(save),
+            None => {
+                game.difficulty = replay.difficulty;
+                game.reset(replay.seed);
+            }
+        }
+        let mut player = Self {
+            replay,
+            mission_idx: 0,
+            run_idx: 0,
+            run_offset: 0,
+            tick: 0,
+            live_tuning,
+            live_difficulty,
+        };
+        player.start_current_mission(game);
+        player
+    }
+
+    // Puts back the tuning and difficulty the player was using before the replay
+    pub fn stop(self, game: &mut Game) {
+        game.set_tuning(self.live_tuning);
+        game.difficulty = self.live_difficulty;
+    }
+
+    // tuning.json changed while the replay plays; applied once it stops
+    pub fn set_live_tuning(&mut self, tuning: Tuning) {
+        self.live_tuning = tuning;
+    }
+
+    fn start_current_mission(&mut self, game: &mut Game) {
+        self.tick = 0;
+        if let Some(track) = self.replay.missions.get(self.mission_idx) {
+            game.arena = vec2(track.arena.0, track.arena.1);
+            if let Some(loadout) = &track.loadout {
+                game.ship.upgrades = loadout.upgrades.clone();
+                game.ship.scrap = loadout.scrap;
+                game.ship.rare_metal = loadout.rare_metal;
+                game.apply_upgrades();
+            }
+        }
+        game.start_mission();
+    }
+
+    fn next_input(&mut self) -> Option<PlayerInput> {
+        let track = self.replay.missions.get(self.mission_idx)?;
+        let &(code, count) = track.inputs.get(self.run_idx)?;
+        self.run_offset += 1;
+        if self.run_offset >= count {
+            self.run_idx += 1;
+            self.run_offset = 0;
+        }
+        Some(PlayerInput::from_code(code))
+    }
+
+    // Mirrors one iteration of the live fixed-step loop
+    pub fn step(&mut self, game: &mut Game) -> ReplayStep {
+        if game.is_mission_complete() {
+            // The live game goes through the success and briefing screens here
+            self.mission_idx += 1;
+            self.run_idx = 0;
+            self.run_offset = 0;
+            if self.mission_idx >= self.replay.missions.len() {
+                return ReplayStep::Finished;
+            }
+            game.next_mission();
+            self.start_current_mission(game);
+        }
+
+        let Some(input) = self.next_input() else {
+            return ReplayStep::Finished;
+        };
+        let track = &self.replay.missions[self.mission_idx];
+        if let Some((_, tuning)) = track.tuning.iter().find(|(at, _)| *at == self.tick) {
+            game.set_tuning(tuning.clone());
+        }
+        self.tick += 1;
+        if update_game(game, &input, TICK_DT) {
+            return ReplayStep::GameOver;
+        }
+        if input.pause {
+            ReplayStep::Paused
+        } else {
+            ReplayStep::Running
+        }
+    }
+}
@@ -1,16 +1,19 @@
-use crate::localization::Localization;
+use crate::data::{DataError, GameData};
+use crate::fonts::FontSet;
+use crate::localization::{Localization, LANG_DIR};
+use crate::settings::Settings;
 use macroquad::prelude::*;
+use std::path::Path;
 
 pub struct Resources {
     pub logo: Texture2D,
     pub background: Texture2D,
-    pub font: Option<Font>,
+    pub fonts: FontSet,
     pub lang: Localization,
 
     pub ship_body: Texture2D,
     pub ship_flame: Texture2D,
-    pub enemy_small: Texture2D,
-    pub enemy_kamikaze: Texture2D,
+    pub enemy_sprites: Vec<Texture2D>, // One per enemy archetype, in data order
     pub bullet: Texture2D,
     pub enemy_bullet: Texture2D,
     pub loot_scrap: Texture2D,
@@ -26,68 +29,66 @@ pub struct Resources {
 }
 
 impl Resources {
-    // Async constructor that will load everything at once
-    pub async fn new() -> Self {
-        let logo = load_texture("assets/logo.png").await.unwrap();
+    // Async constructor that will load everything at once. Fails on the first
+    // texture that is missing or cannot be decoded.
+    pub async fn new(data: &GameData, settings: &Settings) -> Result<Self, DataError> {
+        let logo = texture("assets/logo.png").await?;
         logo.set_filter(FilterMode::Nearest);
 
-        // Try to load font, use None if it fails (will fall back to default font in draw_text_ex)
-        let font = load_ttf_font("assets/Press_Start_2P/PressStart2P-Regular.ttf")
-            .await
-            .ok();
+        // Per-language font fallback chains (missing fonts fall back to the built-in font)
+        let fonts = FontSet::load().await;
 
-        let background = load_texture("assets/space_bg.png").await.unwrap();
+        let background = texture("assets/space_bg.png").await?;
         background.set_filter(FilterMode::Nearest);
 
-        let bullet: Texture2D = load_texture("assets/bullet.png").await.unwrap();
+        let bullet: Texture2D = texture("assets/bullet.png").await?;
         bullet.set_filter(FilterMode::Nearest);
 
-        let enemy_bullet: Texture2D = load_texture("assets/enemy_bullet.png").await.unwrap();
+        let enemy_bullet: Texture2D = texture("assets/enemy_bullet.png").await?;
         enemy_bullet.set_filter(FilterMode::Nearest);
 
-        let ship_body = load_texture("assets/ship_body.png").await.unwrap();
+        let ship_body = texture("assets/ship_body.png").await?;
         ship_body.set_filter(FilterMode::Nearest);
 
-        let ship_flame = load_texture("assets/ship_flame.png").await.unwrap();
+        let ship_flame = texture("assets/ship_flame.png").await?;
         ship_flame.set_filter(FilterMode::Nearest);
 
-        let enemy_small = load_texture("assets/enemy.png").await.unwrap();
-        enemy_small.set_filter(FilterMode::Nearest);
-
-        let enemy_kamikaze = load_texture("assets/enemy_kamikaze.png").await.unwrap();
-        enemy_kamikaze.set_filter(FilterMode::Nearest);
-
-        let loot_scrap = load_texture("assets/loot/resources/rust_pile.png")
-            .await
-            .unwrap();
-        let loot_rare = load_texture("assets/loot/resources/gold.png")
-            .await
-            .unwrap();
-        let loot_health = load_texture("assets/loot/health.png").await.unwrap();
-        let loot_rapid_fire = load_texture("assets/loot/energy.png").await.unwrap();
-        let loot_big_bullet = load_texture("assets/loot/bigger-ammo.png").await.unwrap();
-        let loot_shield = load_texture("assets/loot/shield.png").await.unwrap();
-        let shield_active = load_texture("assets/shield.png").await.unwrap();
+        let mut enemy_sprites = Vec::new();
+        for archetype in &data.enemies.archetypes {
+            let sprite = texture(&archetype.sprite).await?;
+            sprite.set_filter(FilterMode::Nearest);
+            enemy_sprites.push(sprite);
+        }
+
+        let loot_scrap = texture("assets/loot/resources/rust_pile.png").await?;
+        let loot_rare = texture("assets/loot/resources/gold.png").await?;
+        let loot_health = texture("assets/loot/health.png").await?;
+        let loot_rapid_fire = texture("assets/loot/energy.png").await?;
+        let loot_big_bullet = texture("assets/loot/bigger-ammo.png").await?;
+        let loot_shield = texture("assets/loot/shield.png").await?;
+        let shield_active = texture("assets/shield.png").await?;
         shield_active.set_filter(FilterMode::Nearest);
 
-        let asteroid = load_texture("assets/asteroid.png").await.unwrap();
+        let asteroid = texture("assets/asteroid.png").await?;
         asteroid.set_filter(FilterMode::Nearest);
 
-        let rare_asteroid = load_texture("assets/rare_asteroid.png").await.unwrap();
+        let rare_asteroid = texture("assets/rare_asteroid.png").await?;
         rare_asteroid.set_filter(FilterMode::Nearest);
 
-        let explosion = load_texture("assets/explosion.png").await.unwrap();
+        let explosion = texture("assets/explosion.png").await?;
         explosion.set_filter(FilterMode::Nearest);
 
-        Self {
+        let mut lang = Localization::load(Path::new(LANG_DIR));
+        lang.current_lang = settings.language;
+
+        Ok(Self {
             logo,
             background,
-            font,
-            lang: Localization::new(),
+            fonts,
+            lang,
             ship_body,
             ship_flame,
-            enemy_small,
-            enemy_kamikaze,
+            enemy_sprites,
             bullet,
             enemy_bullet,
             loot_scrap,
@@ -100,6 +101,15 @@ impl Resources {
             asteroid,
             rare_asteroid,
             explosion,
-        }
+        })
     }
 }
+
+async fn texture(path: &str) -> Result<Texture2D, DataError> {
+    load_texture(path)
+        .await
+        .map_err(|source| DataError::Texture {
+            path: path.into(),
+            source,
+        })
+}
@@ -0,0 +1,110 @@
+use crate::components::Difficulty;
+use crate::input::Bindings;
+use crate::localization::Language;
+use crate::storage::{self, save_path, StorageError};
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+const SETTINGS_FILE: &str = "settings.json";
+
+// Player preferences, saved whenever one of them changes. Fields missing from
+// the file take their default, so older files keep loading as options are added.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct Settings {
+    pub language: Language,
+    pub difficulty: Difficulty,
+    pub volume: f32, // Master volume from 0 to 1, for sound effects and music
+    pub window_mode: WindowMode,
+    pub controls: Bindings,
+    pub control_scheme: ControlScheme,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowMode {
+    Windowed,
+    Fullscreen,
+}
+
+impl WindowMode {
+    pub fn toggle(self) -> Self {
+        match self {
+            WindowMode::Windowed => WindowMode::Fullscreen,
+            WindowMode::Fullscreen => WindowMode::Windowed,
+        }
+    }
+
+    // Localization key of the mode's name
+    pub fn name_key(self) -> &'static str {
+        match self {
+            WindowMode::Windowed => "window_windowed",
+            WindowMode::Fullscreen => "window_fullscreen",
+        }
+    }
+}
+
+// How the ship is steered. The mouse schemes turn the ship towards the cursor
+// and fire with the left button; the bound keys keep working alongside, and
+// holding a rotate key turns the ship by hand instead of towards the cursor.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ControlScheme {
+    Keyboard,
+    MouseAim,       // Thrust (bound key or right button) pushes where the ship faces
+    MouseAimScreen, // The screen thrust actions push up/down/left/right on the screen
+}
+
+impl ControlScheme {
+    pub fn next(self) -> Self {
+        match self {
+            ControlScheme::Keyboard => ControlScheme::MouseAim,
+            ControlScheme::MouseAim => ControlScheme::MouseAimScreen,
+            ControlScheme::MouseAimScreen => ControlScheme::Keyboard,
+        }
+    }
+
+    pub fn uses_mouse(self) -> bool {
+        self != ControlScheme::Keyboard
+    }
+
+    // Localization key of the scheme's name
+    pub fn name_key(self) -> &'static str {
+        match self {
+            ControlScheme::Keyboard => "scheme_keyboard",
+            ControlScheme::MouseAim => "scheme_mouse_aim",
+            ControlScheme::MouseAimScreen => "scheme_mouse_aim_screen",
+        }
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            language: Language::English,
+            difficulty: Difficulty::Supernova,
+            volume: 1.0,
+            window_mode: WindowMode::Windowed,
+            controls: Bindings::default(),
+            control_scheme: ControlScheme::Keyboard,
+        }
+    }
+}
+
+impl Settings {
+    // Default settings when nothing was saved yet
+    pub fn load() -> Result<Self, StorageError> {
+        let mut settings: Settings =
+            storage::read_json(&save_path(SETTINGS_FILE))?.unwrap_or_default();
+        settings.volume = settings.volume.clamp(0.0, 1.0);
+        Ok(settings)
+    }
+
+    pub fn save(&self) -> Result<(), StorageError> {
+        storage::write_json(&save_path(SETTINGS_FILE), self)
+    }
+
+    pub fn apply_window_mode(&self) {
+        set_fullscreen(self.window_mode == WindowMode::Fullscreen);
+    }
+}
@@ -0,0 +1,78 @@
+use crate::systems::WRAP_MARGIN;
+use macroquad::prelude::*;
+
+// Uniform grid broadphase. Entities are bucketed by the cells their bounding
+// circle overlaps, so a query only has to look at nearby buckets instead of
+// every entity in the arena.
+//
+// The grid covers the band wrapping entities move in (from -WRAP_MARGIN to
+// arena + WRAP_MARGIN, see systems::wrap_around) with a whole number of cells,
+// and cell coordinates wrap around it. Circles that reach across the seam
+// therefore still come back as candidates, but the exact checks measure the
+// plain distance: only what touches on screen collides.
+// Entities outside the band (enemies flying in) fold into it and only cost an
+// extra candidate.
+pub struct SpatialGrid {
+    cell_size: Vec2, // Close to the requested size, stretched to fit the band exactly
+    cols: i32,
+    rows: i32,
+    cells: Vec<Vec<usize>>,
+}
+
+impl SpatialGrid {
+    pub fn new(arena: Vec2, cell_size: f32) -> Self {
+        let band = arena + Vec2::splat(2.0 * WRAP_MARGIN);
+        let cols = ((band.x / cell_size).ceil() as i32).max(1);
+        let rows = ((band.y / cell_size).ceil() as i32).max(1);
+        Self {
+            cell_size: vec2(band.x / cols as f32, band.y / rows as f32),
+            cols,
+            rows,
+            cells: vec![Vec::new(); (cols * rows) as usize],
+        }
+    }
+
+    // Adds an entity (by its index in the owning Vec) to every cell its circle touches
+    pub fn insert(&mut self, id: usize, pos: Vec2, radius: f32) {
+        let (cols, rows) = self.cell_span(pos, radius);
+        for row in rows.clone() {
+            for col in cols.clone() {
+                let cell = self.cell_index(col, row);
+                self.cells[cell].push(id);
+            }
+        }
+    }
+
+    // Collects the ids of all entities that may overlap the given circle.
+    // Candidates still need an exact distance check. Ids come back sorted
+    // and without duplicates so callers can resolve hits in a stable order.
+    pub fn query(&self, pos: Vec2, radius: f32, out: &mut Vec<usize>) {
+        out.clear();
+        let (cols, rows) = self.cell_span(pos, radius);
+        for row in rows {
+            for col in cols.clone() {
+                out.extend_from_slice(&self.cells[self.cell_index(col, row)]);
+            }
+        }
+        out.sort_unstable();
+        out.dedup();
+    }
+
+    // Range of (unwrapped) cell coordinates covered by a circle. Spans wider than
+    // the grid are clamped so every cell is visited once.
+    fn cell_span(&self, pos: Vec2, radius: f32) -> (std::ops::Range<i32>, std::ops::Range<i32>) {
+        let span = |center: f32, cell: f32, count: i32| {
+            let first = ((center + WRAP_MARGIN - radius) / cell).floor() as i32;
+            let last = ((center + WRAP_MARGIN + radius) / cell).floor() as i32;
+            first..(last + 1).min(first + count)
+        };
+        (
+            span(pos.x, self.cell_size.x, self.cols),
+            span(pos.y, self.cell_size.y, self.rows),
+        )
+    }
+
+    fn cell_index(&self, col: i32, row: i32) -> usize {
+        (row.rem_euclid(self.rows) * self.cols + col.rem_euclid(self.cols)) as usize
+    }
+}
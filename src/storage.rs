@@ -0,0 +1,164 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+// Folder inside the platform data directory (XDG_DATA_HOME, ~/.local/share on Linux)
+const APP_DIR: &str = "rust-in-space";
+
+#[derive(Debug)]
+pub enum StorageError {
+    Io {
+        path: PathBuf,
+        source: io::Error,
+    },
+    Parse {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+    // The file was written by a version of the game this one cannot read
+    Version {
+        path: PathBuf,
+        found: u32,
+        expected: u32,
+    },
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageError::Io { path, source } => {
+                write!(f, "cannot access {}: {source}", path.display())
+            }
+            StorageError::Parse { path, source } => {
+                write!(f, "{} is not valid: {source}", path.display())
+            }
+            StorageError::Version {
+                path,
+                found,
+                expected,
+            } => write!(
+                f,
+                "{} has version {found}, expected {expected}",
+                path.display()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for StorageError {}
+
+// Where save files live. Falls back to the working directory on platforms
+// without a data directory.
+pub fn save_dir() -> PathBuf {
+    dirs::data_dir()
+        .map(|dir| dir.join(APP_DIR))
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+pub fn save_path(file: &str) -> PathBuf {
+    save_dir().join(file)
+}
+
+// The previous version of a save file, kept next to it
+pub fn backup_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".bak");
+    path.with_file_name(name)
+}
+
+fn temp_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+    path.with_file_name(name)
+}
+
+fn io_error(path: &Path) -> impl FnOnce(io::Error) -> StorageError + '_ {
+    move |source| StorageError::Io {
+        path: path.to_path_buf(),
+        source,
+    }
+}
+
+// Copies a save file the game used to keep in the working directory into the
+// data directory, unless the data directory already has one. Returns true if a
+// file was copied. Without a data directory both are the same file, which then
+// already exists.
+pub fn import_legacy(path: &Path, legacy: &Path) -> Result<bool, StorageError> {
+    if path.exists() || backup_path(path).exists() || !legacy.is_file() {
+        return Ok(false);
+    }
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(io_error(dir))?;
+    }
+    fs::copy(legacy, path).map_err(io_error(path))?;
+    Ok(true)
+}
+
+// Reads a save file, None if it does not exist. A file that cannot be read or
+// parsed is replaced by its backup, if the backup is good.
+pub fn read_json<T: DeserializeOwned>(path: &Path) -> Result<Option<T>, StorageError> {
+    let error = match read_file(path) {
+        Ok(value) => return Ok(value),
+        Err(e) => e,
+    };
+    match read_file(&backup_path(path)) {
+        Ok(Some(value)) => {
+            macroquad::logging::warn!("{}, using the backup instead", error);
+            Ok(Some(value))
+        }
+        _ => Err(error),
+    }
+}
+
+fn read_file<T: DeserializeOwned>(path: &Path) -> Result<Option<T>, StorageError> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(io_error(path)(e)),
+    };
+    serde_json::from_str(&content)
+        .map(Some)
+        .map_err(|source| StorageError::Parse {
+            path: path.to_path_buf(),
+            source,
+        })
+}
+
+// Writes a save file so that a crash leaves either the old or the new content:
+// the data goes to a temporary file that then replaces the real one. The old
+// content is kept as the backup, unless it is unreadable.
+pub fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<(), StorageError> {
+    let json = serde_json::to_string_pretty(value).map_err(|source| StorageError::Parse {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(io_error(dir))?;
+    }
+
+    let temp = temp_path(path);
+    let mut file = fs::File::create(&temp).map_err(io_error(&temp))?;
+    file.write_all(json.as_bytes())
+        .and_then(|_| file.sync_all())
+        .map_err(io_error(&temp))?;
+
+    if read_file::<serde_json::Value>(path).is_ok_and(|old| old.is_some()) {
+        let backup = backup_path(path);
+        fs::copy(path, &backup).map_err(io_error(&backup))?;
+    }
+    fs::rename(&temp, path).map_err(io_error(path))
+}
+
+// Deletes a save file and its backup; files that are already gone are fine
+pub fn remove(path: &Path) -> Result<(), StorageError> {
+    for file in [path.to_path_buf(), backup_path(path)] {
+        match fs::remove_file(&file) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(io_error(&file)(e)),
+            _ => {}
+        }
+    }
+    Ok(())
+}
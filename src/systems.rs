@@ -1,186 +1,183 @@
-use crate::components::Mission;
-use crate::components::SaveData;
+use crate::components::{
+    CampaignSave, SaveData, ScoreEntry, CAMPAIGN_VERSION, DEFAULT_PILOT_NAME, SAVE_DATA_VERSION,
+};
 use crate::components::{Difficulty, LootItem, LootType};
+use crate::loot::LootTables;
+use crate::storage::{self, save_path, StorageError};
 use macroquad::prelude::*;
-use macroquad::rand::gen_range;
-use std::fs;
+use macroquad::rand::RandGenerator;
+use serde::Deserialize;
+use std::path::Path;
 
 // Where does the item drop from?
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum LootSource {
     Asteroid,
     RareAsteroid,
+    #[serde(rename = "enemy")]
     EnemySmall,
     // EnemyBoss, // For future
 }
 
-pub fn wrap_around(pos: &mut Vec2) {
-    if pos.x < -20.0 {
-        pos.x = screen_width() + 20.0;
-    } else if pos.x > screen_width() + 20.0 {
-        pos.x = -20.0;
+// How far past the edge of the arena wrapping entities go before they come
+// back on the other side
+pub const WRAP_MARGIN: f32 = 20.0;
+
+pub fn wrap_around(pos: &mut Vec2, arena: Vec2) {
+    if pos.x < -WRAP_MARGIN {
+        pos.x = arena.x + WRAP_MARGIN;
+    } else if pos.x > arena.x + WRAP_MARGIN {
+        pos.x = -WRAP_MARGIN;
     }
 
-    if pos.y < -20.0 {
-        pos.y = screen_height() + 20.0;
-    } else if pos.y > screen_height() + 20.0 {
-        pos.y = -20.0;
+    if pos.y < -WRAP_MARGIN {
+        pos.y = arena.y + WRAP_MARGIN;
+    } else if pos.y > arena.y + WRAP_MARGIN {
+        pos.y = -WRAP_MARGIN;
     }
 }
 
+// Signed difference between two angles in degrees, in the range -180..180
+pub fn angle_diff(from: f32, to: f32) -> f32 {
+    (to - from + 540.0).rem_euclid(360.0) - 180.0
+}
+
 const SAVE_FILE: &str = "highscore.json";
 
-pub fn save_score(score: u32) {
-    let current_data = load_score();
-    if score > current_data.high_score {
-        let new_data = SaveData { high_score: score };
-        if let Ok(json) = serde_json::to_string(&new_data) {
-            let _ = fs::write(SAVE_FILE, json);
-        }
-    }
+// Writes the leaderboards, see storage::write_json
+pub fn save_score(data: &SaveData) -> Result<(), StorageError> {
+    storage::write_json(&save_path(SAVE_FILE), data)
 }
 
-pub fn load_score() -> SaveData {
-    if let Ok(content) = fs::read_to_string(SAVE_FILE) {
-        if let Ok(data) = serde_json::from_str::<SaveData>(&content) {
-            return data;
+// Empty leaderboards when nothing was saved yet. A single high score from before
+// the leaderboards goes onto the board of `difficulty`, the one the player has selected.
+pub fn load_score(difficulty: Difficulty) -> Result<SaveData, StorageError> {
+    let path = save_path(SAVE_FILE);
+    // Older versions kept the high score in the working directory
+    if storage::import_legacy(&path, Path::new(SAVE_FILE))? {
+        info!("Imported {} into {}", SAVE_FILE, path.display());
+    }
+    let Some(mut value) = storage::read_json::<serde_json::Value>(&path)? else {
+        return Ok(SaveData::default());
+    };
+    let version = value["version"].as_u64().unwrap_or(1) as u32;
+    let mut legacy_score = None;
+    if version == 1 {
+        legacy_score = value["high_score"].as_u64().filter(|&score| score > 0);
+        if let Some(object) = value.as_object_mut() {
+            object.remove("high_score");
         }
+        value["version"] = SAVE_DATA_VERSION.into();
+    } else if version != SAVE_DATA_VERSION {
+        return Err(StorageError::Version {
+            path,
+            found: version,
+            expected: SAVE_DATA_VERSION,
+        });
+    }
+    let mut data: SaveData =
+        serde_json::from_value(value).map_err(|source| StorageError::Parse { path, source })?;
+    if let Some(score) = legacy_score {
+        data.add(
+            difficulty,
+            ScoreEntry {
+                name: DEFAULT_PILOT_NAME.to_string(),
+                score: u32::try_from(score).unwrap_or(u32::MAX),
+                level: 0,
+                date: String::new(),
+            },
+        );
     }
-    SaveData::new()
+    Ok(data)
+}
+
+const CAMPAIGN_FILE: &str = "campaign.json";
+
+pub fn save_campaign(save: &CampaignSave) -> Result<(), StorageError> {
+    storage::write_json(&save_path(CAMPAIGN_FILE), save)
 }
 
-pub fn get_mission(level: u32) -> Mission {
-    match level {
-        1 => Mission {
-            level_id: 1,
-            title: "Operation: Dust".to_string(),
-            description: "Destroy 3 scouts and collect resources.".to_string(),
-            target_kills: 3,
-            target_scrap: 1,
-            target_rare_metal: 0,
-            enemy_spawn_interval: 10.0, // enemies spawn rarely
-            asteroid_count: 5,
-        },
-        2 => Mission {
-            level_id: 2,
-            title: "Into the Void".to_string(),
-            description: "Enemy activity rising. Kill 10 enemies.".to_string(),
-            target_kills: 10,
-            target_scrap: 0, // scrap is not important
-            target_rare_metal: 0,
-            enemy_spawn_interval: 2.0,
-            asteroid_count: 8,
-        },
-        3 => Mission {
-            level_id: 3,
-            title: "Scrap Yard".to_string(),
-            description: "Collect 20 rust piles and 3 gold for upgrades.".to_string(),
-            target_kills: 5,
-            target_scrap: 20,
-            target_rare_metal: 3,
-            enemy_spawn_interval: 2.5,
-            asteroid_count: 12,
-        },
-        _ => Mission {
-            // generate infinite levels after the 3rd one
-            level_id: level,
-            title: format!("Deep Space sector {level}"),
-            description: "Survive.".to_string(),
-            target_kills: 10 + level,
-            target_scrap: 10 + (level / 2),
-            target_rare_metal: 2 + (level / 3),
-            enemy_spawn_interval: (1.5 - (level as f32 * 0.1)).max(0.5),
-            asteroid_count: 10 + level as usize,
-        },
+// None when there is no campaign to continue. The version is checked before
+// the rest of the file, so older saves can be migrated here once the format changes.
+pub fn load_campaign() -> Result<Option<CampaignSave>, StorageError> {
+    let path = save_path(CAMPAIGN_FILE);
+    let Some(mut value) = storage::read_json::<serde_json::Value>(&path)? else {
+        return Ok(None);
+    };
+    let version = value["version"].as_u64().unwrap_or(0) as u32;
+    if version == 1 {
+        // Saved before the Hangar existed; the missing upgrades default to none
+        value["version"] = CAMPAIGN_VERSION.into();
+    } else if version != CAMPAIGN_VERSION {
+        return Err(StorageError::Version {
+            path,
+            found: version,
+            expected: CAMPAIGN_VERSION,
+        });
     }
+    serde_json::from_value(value)
+        .map(Some)
+        .map_err(|source| StorageError::Parse { path, source })
 }
 
-pub fn generate_loot(pos: Vec2, source: LootSource, difficulty: Difficulty) -> Option<LootItem> {
-    // Base roll (0-100) + Difficulty modifier
-    // On easy (Nebula) roll will be 10-110 (more chances on rare)
-    // On hard (Black Hole) roll will be -15-85 (less chances)
-    let roll = gen_range(0, 100) + difficulty.loot_luck_modifier();
-
-    let (item_type, radius) = match source {
-        LootSource::Asteroid => {
-            if roll < 40 {
-                (LootType::Scrap(gen_range(1, 4)), 10.0)
-            }
-            // If roll became less due to difficulty, RareMetal will drop less
-            else if roll < 45 {
-                (LootType::RareMetal(1), 12.0)
-            } else {
-                return None;
-            }
-        }
-        LootSource::RareAsteroid => {
-            // Rare asteroids always drop loot (100% chance)
-            if roll < 30 {
-                (LootType::RareMetal(gen_range(2, 5)), 12.0)
-            }
-            // 30% chance of rare metal
-            else if roll < 45 {
-                (LootType::Scrap(gen_range(5, 10)), 10.0)
-            }
-            // 15% chance of scrap
-            else if roll < 70 {
-                (LootType::HealthPack(25), 15.0)
-            }
-            // 25% chance of health pack
-            else if roll < 88 {
-                (LootType::RapidFireBoost, 15.0)
-            }
-            // 18% chance of rapid fire boost
-            else {
-                // 12% chance of big bullet boost (roll 88-99)
-                (LootType::BigBulletBoost, 15.0)
-            }
-        }
-        LootSource::EnemySmall => {
-            if roll < 30 {
-                (LootType::Scrap(gen_range(5, 10)), 10.0)
-            }
-            // 30% chance of scrap
-            else if roll < 55 {
-                (LootType::HealthPack(25), 15.0)
-            }
-            // 25% health pack
-            else if roll < 73 {
-                (LootType::RapidFireBoost, 15.0)
-            }
-            // 18% rapid fire boost
-            else if roll < 85 {
-                (LootType::BigBulletBoost, 15.0)
-            }
-            // 12% big bullet boost
-            else if roll < 93 {
-                // 8% chance of shield with varying HP (30-100 HP)
-                (LootType::Shield(gen_range(30, 101)), 15.0)
-            } else {
-                return None;
-            }
-            // 7% chance of nothing
-        } // LootSource::EnemyBoss => {
-          //     // Something always drops from the boss
-          //     (LootType::RareMetal(gen_range(10, 50)), 20.0)
-          // }
+// Called when the campaign ends, so Continue is no longer offered
+pub fn clear_campaign() -> Result<(), StorageError> {
+    storage::remove(&save_path(CAMPAIGN_FILE))
+}
+
+// Today's date as YYYY-MM-DD (UTC), for leaderboard entries
+pub fn today() -> String {
+    let days = (macroquad::miniquad::date::now() / 86400.0).floor() as i64;
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+// Picks a fresh seed for a run when none was requested on the command line
+pub fn random_seed() -> u64 {
+    (macroquad::miniquad::date::now() * 1000.0) as u64
+}
+
+pub fn generate_loot(
+    pos: Vec2,
+    source: LootSource,
+    difficulty: Difficulty,
+    tables: &LootTables,
+    rng: &RandGenerator,
+) -> Option<LootItem> {
+    let item_type = tables.roll(source, difficulty, rng)?;
+    let radius = match item_type {
+        LootType::Scrap(_) => 10.0,
+        LootType::RareMetal(_) => 12.0,
+        _ => 15.0,
     };
 
     // Random slow drift velocity (super slow, like floating in space)
-    let drift_speed = gen_range(5.0, 15.0);
-    let drift_angle = gen_range(0.0, std::f32::consts::PI * 2.0);
+    let drift_speed = rng.gen_range(5.0, 15.0);
+    let drift_angle = rng.gen_range(0.0, std::f32::consts::PI * 2.0);
     let drift_vel = vec2(drift_angle.cos(), drift_angle.sin()) * drift_speed;
 
     // Random rotation speed (can be positive or negative for random direction)
-    let rotation_speed = gen_range(-1.5, 1.5);
+    let rotation_speed = rng.gen_range(-1.5, 1.5);
 
     Some(LootItem {
         pos,
-        vel: vec2(gen_range(-50.0, 50.0), gen_range(-50.0, 50.0)), // Fly apart on explosion
+        prev_pos: pos,
+        vel: vec2(rng.gen_range(-50.0, 50.0), rng.gen_range(-50.0, 50.0)), // Fly apart on explosion
         drift_vel,
         item_type,
         radius,
         magnet_active: false,
-        rotation: gen_range(0.0, std::f32::consts::PI * 2.0), // Random initial rotation
+        rotation: rng.gen_range(0.0, std::f32::consts::PI * 2.0), // Random initial rotation
         rotation_speed,
     })
 }
@@ -0,0 +1,90 @@
+use crate::data::{load_validated, DataError, Validate};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+// Feel-tuning values from tuning.json. The game watches the file and picks up
+// changes while it is running (see TuningWatcher). Replays store the values
+// they were recorded with.
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Tuning {
+    pub rotation_speed: f32, // Degrees per second
+    pub acceleration: f32,
+    pub reverse_acceleration: f32, // Of the reverse thrusters
+    pub brake_deceleration: f32,   // Speed the inertial dampeners take off per second
+    pub max_speed: f32,            // Pixels per second the engines cannot push the ship past
+    pub bullet_speed: f32,
+    pub bullet_lifetime: f32,
+    pub shoot_cooldown: f32, // Seconds between shots, a third of it with rapid fire
+    pub player_bullet_damage: f32,
+    pub player_bullet_radius: f32,
+    pub big_bullet_damage: f32,
+    pub big_bullet_radius: f32,
+    pub asteroid_damage: f32, // Per 10 units of asteroid radius, before the difficulty multiplier
+    pub magnet_radius: f32,   // Loot closer than this starts flying to the ship
+    pub magnet_speed: f32,
+}
+
+impl Validate for Tuning {
+    fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        for (field, value) in [
+            ("max_speed", self.max_speed),
+            ("bullet_speed", self.bullet_speed),
+            ("bullet_lifetime", self.bullet_lifetime),
+            ("shoot_cooldown", self.shoot_cooldown),
+            ("player_bullet_radius", self.player_bullet_radius),
+            ("big_bullet_radius", self.big_bullet_radius),
+        ] {
+            if value <= 0.0 {
+                problems.push(format!("`{field}` must be greater than 0, got {value}"));
+            }
+        }
+        for (field, value) in [
+            ("rotation_speed", self.rotation_speed),
+            ("acceleration", self.acceleration),
+            ("reverse_acceleration", self.reverse_acceleration),
+            ("brake_deceleration", self.brake_deceleration),
+            ("player_bullet_damage", self.player_bullet_damage),
+            ("big_bullet_damage", self.big_bullet_damage),
+            ("asteroid_damage", self.asteroid_damage),
+            ("magnet_radius", self.magnet_radius),
+            ("magnet_speed", self.magnet_speed),
+        ] {
+            if value < 0.0 {
+                problems.push(format!("`{field}` must not be negative, got {value}"));
+            }
+        }
+        problems
+    }
+}
+
+// Reloads tuning.json when its modification time changes
+pub struct TuningWatcher {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+}
+
+impl TuningWatcher {
+    pub fn new(path: PathBuf) -> Self {
+        let modified = modified_time(&path);
+        Self { path, modified }
+    }
+
+    // Returns the freshly loaded values if the file changed since the last call.
+    // A file that fails to load is reported once and retried after its next change.
+    pub fn poll(&mut self) -> Option<Result<Tuning, DataError>> {
+        let modified = modified_time(&self.path);
+        if modified == self.modified {
+            return None;
+        }
+        self.modified = modified;
+        Some(load_validated(&self.path))
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
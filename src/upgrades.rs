@@ -0,0 +1,159 @@
+use crate::data::Validate;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// Ship upgrades sold in the Hangar between missions
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Upgrade {
+    MaxHealth,
+    EngineRampUp,
+    FireRate,
+    BulletDamage,
+    MagnetRadius,
+    ShieldCapacity,
+}
+
+impl Upgrade {
+    // In the order of the Hangar screen
+    pub const ALL: [Upgrade; 6] = [
+        Upgrade::MaxHealth,
+        Upgrade::EngineRampUp,
+        Upgrade::FireRate,
+        Upgrade::BulletDamage,
+        Upgrade::MagnetRadius,
+        Upgrade::ShieldCapacity,
+    ];
+
+    // Localization key of the upgrade's name
+    pub fn name_key(self) -> &'static str {
+        match self {
+            Upgrade::MaxHealth => "upgrade_max_health",
+            Upgrade::EngineRampUp => "upgrade_engine_ramp_up",
+            Upgrade::FireRate => "upgrade_fire_rate",
+            Upgrade::BulletDamage => "upgrade_bullet_damage",
+            Upgrade::MagnetRadius => "upgrade_magnet_radius",
+            Upgrade::ShieldCapacity => "upgrade_shield_capacity",
+        }
+    }
+
+    // Localization key of what one level adds, with the amount as {value}
+    pub fn effect_key(self) -> &'static str {
+        match self {
+            Upgrade::MaxHealth => "upgrade_max_health_effect",
+            Upgrade::EngineRampUp => "upgrade_engine_ramp_up_effect",
+            Upgrade::FireRate => "upgrade_fire_rate_effect",
+            Upgrade::BulletDamage => "upgrade_bullet_damage_effect",
+            Upgrade::MagnetRadius => "upgrade_magnet_radius_effect",
+            Upgrade::ShieldCapacity => "upgrade_shield_capacity_effect",
+        }
+    }
+
+    // Effects that are a share of the base value, shown as percentages
+    pub fn is_percentage(self) -> bool {
+        matches!(
+            self,
+            Upgrade::FireRate | Upgrade::BulletDamage | Upgrade::ShieldCapacity
+        )
+    }
+}
+
+// Prices and effects from upgrades.json
+#[derive(Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct UpgradeSet {
+    pub max_health: UpgradeDef,      // Effect: HP added
+    pub engine_ramp_up: UpgradeDef,  // Effect: added to the engine's ramp-up per second
+    pub fire_rate: UpgradeDef,       // Effect: share of the shot cooldown removed
+    pub bullet_damage: UpgradeDef,   // Effect: share of bullet damage added
+    pub magnet_radius: UpgradeDef,   // Effect: pixels added to the loot magnet radius
+    pub shield_capacity: UpgradeDef, // Effect: share of shield HP added
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct UpgradeDef {
+    pub effect: f32,       // Added by every level
+    pub levels: Vec<Cost>, // Price of each level in order; their count is the maximum level
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Cost {
+    pub scrap: u32,
+    pub rare_metal: u32,
+}
+
+// Levels bought so far, saved with the campaign. Upgrades never bought are left out.
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, Debug)]
+#[serde(transparent)]
+pub struct Upgrades {
+    levels: BTreeMap<Upgrade, u32>,
+}
+
+impl Upgrades {
+    pub fn level(&self, upgrade: Upgrade) -> u32 {
+        self.levels.get(&upgrade).copied().unwrap_or(0)
+    }
+
+    pub fn raise(&mut self, upgrade: Upgrade) {
+        *self.levels.entry(upgrade).or_default() += 1;
+    }
+}
+
+impl UpgradeSet {
+    pub fn def(&self, upgrade: Upgrade) -> &UpgradeDef {
+        match upgrade {
+            Upgrade::MaxHealth => &self.max_health,
+            Upgrade::EngineRampUp => &self.engine_ramp_up,
+            Upgrade::FireRate => &self.fire_rate,
+            Upgrade::BulletDamage => &self.bullet_damage,
+            Upgrade::MagnetRadius => &self.magnet_radius,
+            Upgrade::ShieldCapacity => &self.shield_capacity,
+        }
+    }
+
+    // Total effect of the levels bought. Levels beyond the maximum (from an
+    // older upgrades.json) count as the maximum.
+    pub fn bonus(&self, upgrade: Upgrade, upgrades: &Upgrades) -> f32 {
+        let def = self.def(upgrade);
+        def.effect * upgrades.level(upgrade).min(def.levels.len() as u32) as f32
+    }
+
+    // Price of the next level, None once the upgrade is maxed out
+    pub fn next_cost(&self, upgrade: Upgrade, upgrades: &Upgrades) -> Option<Cost> {
+        self.def(upgrade)
+            .levels
+            .get(upgrades.level(upgrade) as usize)
+            .copied()
+    }
+}
+
+impl Validate for UpgradeSet {
+    fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        for upgrade in Upgrade::ALL {
+            let def = self.def(upgrade);
+            let name = format!("{upgrade:?}");
+            if def.effect < 0.0 {
+                problems.push(format!(
+                    "{name}: `effect` must not be negative, got {}",
+                    def.effect
+                ));
+            }
+            if def.levels.is_empty() {
+                problems.push(format!("{name}: needs at least one level"));
+            }
+        }
+        // The cooldown has to stay above zero even at the highest level
+        let fire_rate = self.def(Upgrade::FireRate);
+        let removed = fire_rate.effect * fire_rate.levels.len() as f32;
+        if removed >= 1.0 {
+            problems.push(format!(
+                "FireRate: all levels together remove {:.0}% of the cooldown, must be less than 100%",
+                removed * 100.0
+            ));
+        }
+        problems
+    }
+}
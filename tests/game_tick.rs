@@ -0,0 +1,57 @@
+// Steps a Game headlessly: no window, an explicit arena and scripted input
+use macroquad::prelude::*;
+use rust_in_space::components::PlayerInput;
+use rust_in_space::data::{GameData, DATA_DIR};
+use rust_in_space::game::{update_game, Game, TICK_DT};
+use std::path::Path;
+
+const ARENA: Vec2 = vec2(800.0, 600.0);
+
+fn new_game() -> Game {
+    let data = GameData::load(Path::new(DATA_DIR)).expect("shipped data loads");
+    let mut game = Game::new(ARENA, 42, data);
+    game.start_mission();
+    game
+}
+
+#[test]
+fn ship_starts_in_the_middle_of_the_arena() {
+    let game = new_game();
+    assert_eq!(game.ship.pos, ARENA / 2.0);
+}
+
+#[test]
+fn idle_tick_leaves_the_ship_in_place() {
+    let mut game = new_game();
+    let start = game.ship.pos;
+    assert!(!update_game(&mut game, &PlayerInput::default(), TICK_DT));
+    assert_eq!(game.ship.pos, start);
+    assert!(game.bullets.is_empty());
+}
+
+#[test]
+fn fire_spawns_a_bullet_on_the_first_tick() {
+    let mut game = new_game();
+    let input = PlayerInput {
+        fire: true,
+        ..Default::default()
+    };
+    update_game(&mut game, &input, TICK_DT);
+    assert_eq!(game.bullets.len(), 1);
+}
+
+#[test]
+fn thrust_moves_the_ship_where_it_faces() {
+    let mut game = new_game();
+    let start = game.ship.pos;
+    let input = PlayerInput {
+        thrust: 1.0,
+        ..Default::default()
+    };
+    for _ in 0..30 {
+        update_game(&mut game, &input, TICK_DT);
+    }
+    // Rotation 0 faces right
+    assert!(game.ship.pos.x > start.x);
+    assert!((game.ship.pos.y - start.y).abs() < 0.001);
+}
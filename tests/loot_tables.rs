@@ -0,0 +1,209 @@
+// Checks the shipped loot tables against the drop chances documented in the README
+use macroquad::rand::RandGenerator;
+use rust_in_space::components::{Difficulty, LootType};
+use rust_in_space::data::load_validated;
+use rust_in_space::loot::{LootKind, LootTables};
+use rust_in_space::systems::LootSource;
+use std::path::Path;
+
+const SOURCES: [LootSource; 3] = [
+    LootSource::Asteroid,
+    LootSource::RareAsteroid,
+    LootSource::EnemySmall,
+];
+
+fn load_tables() -> LootTables {
+    load_validated::<LootTables>(Path::new("assets/data/loot.json"))
+        .unwrap_or_else(|e| panic!("{e}"))
+}
+
+fn kind_of(item: &LootType) -> LootKind {
+    match item {
+        LootType::Scrap(_) => LootKind::Scrap,
+        LootType::RareMetal(_) => LootKind::RareMetal,
+        LootType::HealthPack(_) => LootKind::HealthPack,
+        LootType::RapidFireBoost => LootKind::RapidFireBoost,
+        LootType::BigBulletBoost => LootKind::BigBulletBoost,
+        LootType::Shield(_) => LootKind::Shield,
+    }
+}
+
+#[test]
+fn weights_add_up_to_100_percent() {
+    let tables = load_tables();
+    for source in SOURCES {
+        let total: u32 = tables.table(source).iter().map(|e| e.weight).sum();
+        assert_eq!(total, 100, "weights of a table should read as percentages");
+    }
+}
+
+#[test]
+fn chances_match_the_readme() {
+    let tables = load_tables();
+    let expected: [(LootSource, &[(LootKind, f32)]); 3] = [
+        (
+            LootSource::Asteroid,
+            &[
+                (LootKind::Scrap, 0.40),
+                (LootKind::RareMetal, 0.05),
+                (LootKind::Nothing, 0.55),
+            ],
+        ),
+        (
+            LootSource::RareAsteroid,
+            &[
+                (LootKind::RareMetal, 0.30),
+                (LootKind::Scrap, 0.15),
+                (LootKind::HealthPack, 0.25),
+                (LootKind::RapidFireBoost, 0.18),
+                (LootKind::BigBulletBoost, 0.12),
+            ],
+        ),
+        (
+            LootSource::EnemySmall,
+            &[
+                (LootKind::Scrap, 0.30),
+                (LootKind::HealthPack, 0.25),
+                (LootKind::RapidFireBoost, 0.18),
+                (LootKind::BigBulletBoost, 0.12),
+                (LootKind::Shield, 0.08),
+                (LootKind::Nothing, 0.07),
+            ],
+        ),
+    ];
+
+    for (source, chances) in expected {
+        let actual = tables.chances(source, Difficulty::Supernova);
+        assert_eq!(actual.len(), chances.len());
+        for ((kind, chance), (expected_kind, expected_chance)) in actual.iter().zip(chances) {
+            assert_eq!(kind, expected_kind);
+            assert!(
+                (chance - expected_chance).abs() < 1e-6,
+                "{kind:?}: {chance} != {expected_chance}"
+            );
+        }
+    }
+}
+
+#[test]
+fn luck_shifts_chances_between_drops_and_nothing() {
+    let tables = load_tables();
+    let nothing = |difficulty| {
+        tables
+            .chances(LootSource::Asteroid, difficulty)
+            .iter()
+            .find(|(kind, _)| *kind == LootKind::Nothing)
+            .map_or(0.0, |(_, chance)| *chance)
+    };
+    assert!(nothing(Difficulty::Nebula) < nothing(Difficulty::Supernova));
+    assert!(nothing(Difficulty::BlackHole) > nothing(Difficulty::Supernova));
+
+    let rare_metal = |difficulty| {
+        tables
+            .chances(LootSource::Asteroid, difficulty)
+            .iter()
+            .find(|(kind, _)| *kind == LootKind::RareMetal)
+            .map_or(0.0, |(_, chance)| *chance)
+    };
+    // Luck is a weight multiplier, not percentage points: 5% * 1.1 / (45% * 1.1 + 55%)
+    assert!((rare_metal(Difficulty::Nebula) - 0.055 / 1.045).abs() < 1e-6);
+}
+
+#[test]
+fn luck_scales_amounts_in_tables_without_nothing() {
+    let tables = load_tables();
+    let rng = RandGenerator::new();
+    rng.srand(3);
+    let rolls = 100_000;
+
+    // Rare asteroids always drop something, so luck shows in what they drop
+    let average_value = |difficulty| {
+        let total: u32 = (0..rolls)
+            .filter_map(|_| tables.roll(LootSource::RareAsteroid, difficulty, &rng))
+            .map(|item| match item {
+                LootType::Scrap(n) | LootType::RareMetal(n) | LootType::Shield(n) => n,
+                LootType::HealthPack(n) => n as u32,
+                LootType::RapidFireBoost | LootType::BigBulletBoost => 0,
+            })
+            .sum();
+        total as f32 / rolls as f32
+    };
+    let normal = average_value(Difficulty::Supernova);
+    let lucky = average_value(Difficulty::Nebula);
+    let unlucky = average_value(Difficulty::BlackHole);
+    assert!((lucky / normal - 1.1).abs() < 0.01, "{lucky} vs {normal}");
+    assert!(
+        (unlucky / normal - 0.85).abs() < 0.01,
+        "{unlucky} vs {normal}"
+    );
+}
+
+#[test]
+fn rolls_follow_the_chances() {
+    let tables = load_tables();
+    let rng = RandGenerator::new();
+    rng.srand(7);
+    let rolls = 200_000;
+
+    for difficulty in [
+        Difficulty::Nebula,
+        Difficulty::Supernova,
+        Difficulty::BlackHole,
+    ] {
+        for source in SOURCES {
+            let mut counts: Vec<(LootKind, u32)> = Vec::new();
+            for _ in 0..rolls {
+                let kind = tables
+                    .roll(source, difficulty, &rng)
+                    .map_or(LootKind::Nothing, |item| kind_of(&item));
+                match counts.iter_mut().find(|(k, _)| *k == kind) {
+                    Some((_, count)) => *count += 1,
+                    None => counts.push((kind, 1)),
+                }
+            }
+
+            for (kind, chance) in tables.chances(source, difficulty) {
+                let count = counts
+                    .iter()
+                    .find(|(k, _)| *k == kind)
+                    .map_or(0, |(_, count)| *count);
+                let share = count as f32 / rolls as f32;
+                assert!(
+                    (share - chance).abs() < 0.01,
+                    "{kind:?}: rolled {share}, expected {chance}"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn amounts_stay_in_range() {
+    let tables = load_tables();
+    let rng = RandGenerator::new();
+    rng.srand(11);
+
+    for source in SOURCES {
+        for _ in 0..10_000 {
+            let Some(item) = tables.roll(source, Difficulty::Supernova, &rng) else {
+                continue;
+            };
+            let amount = match item {
+                LootType::Scrap(n) | LootType::RareMetal(n) | LootType::Shield(n) => n,
+                LootType::HealthPack(n) => n as u32,
+                LootType::RapidFireBoost | LootType::BigBulletBoost => continue,
+            };
+            let kind = kind_of(&item);
+            let entry = tables
+                .table(source)
+                .iter()
+                .find(|e| e.item == kind)
+                .unwrap();
+            let (min, max) = entry.amount.unwrap();
+            assert!(
+                (min..=max).contains(&amount),
+                "{kind:?}: {amount} outside [{min}, {max}]"
+            );
+        }
+    }
+}
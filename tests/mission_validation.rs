@@ -0,0 +1,161 @@
+// Checks that broken missions.json values are reported with a readable message
+use rust_in_space::data::{load_validated, Validate};
+use rust_in_space::missions::{MissionSet, MAX_ASTEROID_COUNT};
+use std::path::Path;
+
+fn shipped() -> MissionSet {
+    load_validated::<MissionSet>(Path::new("assets/data/missions.json"))
+        .unwrap_or_else(|e| panic!("{e}"))
+}
+
+// Validates a copy of the shipped missions changed by `edit` and expects exactly `message`
+fn assert_problem(edit: impl FnOnce(&mut MissionSet), message: &str) {
+    let mut set = shipped();
+    edit(&mut set);
+    assert_eq!(set.validate(), vec![message.to_string()]);
+}
+
+#[test]
+fn shipped_missions_are_valid() {
+    assert!(shipped().validate().is_empty());
+}
+
+#[test]
+fn reports_missing_missions() {
+    assert_problem(
+        |set| set.missions.clear(),
+        "`missions` must contain at least one mission",
+    );
+}
+
+#[test]
+fn reports_empty_title_key() {
+    assert_problem(
+        |set| set.missions[0].title_key = " ".to_string(),
+        "missions[0] (level 1, \" \"): `title_key` is empty",
+    );
+}
+
+#[test]
+fn reports_empty_description_key() {
+    assert_problem(
+        |set| set.missions[1].description_key.clear(),
+        "missions[1] (level 2, \"mission_void_title\"): `description_key` is empty",
+    );
+}
+
+#[test]
+fn reports_spawn_interval_of_zero() {
+    assert_problem(
+        |set| set.missions[1].enemy_spawn_interval = 0.0,
+        "missions[1] (level 2, \"mission_void_title\"): `enemy_spawn_interval` must be greater than 0, got 0",
+    );
+}
+
+#[test]
+fn reports_mission_without_objective() {
+    assert_problem(
+        |set| {
+            let m = &mut set.missions[0];
+            m.target_kills = 0;
+            m.target_scrap = 0;
+            m.target_rare_metal = 0;
+        },
+        "missions[0] (level 1, \"mission_dust_title\"): needs at least one objective (kills, scrap or rare metal)",
+    );
+}
+
+#[test]
+fn reports_asteroid_count_of_zero() {
+    assert_problem(
+        |set| set.missions[0].asteroid_count = 0,
+        "missions[0] (level 1, \"mission_dust_title\"): `asteroid_count` must be between 1 and 40, got 0",
+    );
+}
+
+#[test]
+fn reports_too_many_asteroids() {
+    assert_problem(
+        |set| set.missions[2].asteroid_count = MAX_ASTEROID_COUNT + 1,
+        "missions[2] (level 3, \"mission_scrap_yard_title\"): `asteroid_count` must be between 1 and 40, got 41",
+    );
+}
+
+#[test]
+fn reports_empty_endless_title_key() {
+    assert_problem(
+        |set| set.endless.title_key.clear(),
+        "endless: `title_key` is empty",
+    );
+}
+
+#[test]
+fn reports_empty_endless_description_key() {
+    assert_problem(
+        |set| set.endless.description_key = "  ".to_string(),
+        "endless: `description_key` is empty",
+    );
+}
+
+#[test]
+fn reports_endless_without_objective() {
+    assert_problem(
+        |set| {
+            let e = &mut set.endless;
+            e.target_kills.base = 0;
+            e.target_scrap.base = 0;
+            e.target_rare_metal.base = 0;
+        },
+        "endless: needs at least one objective with a `base` above 0 (kills, scrap or rare metal)",
+    );
+}
+
+#[test]
+fn reports_endless_every_of_zero() {
+    assert_problem(
+        |set| set.endless.target_scrap.every = 0,
+        "endless.target_scrap: `every` must be at least 1",
+    );
+}
+
+#[test]
+fn reports_endless_asteroid_base_out_of_range() {
+    assert_problem(
+        |set| set.endless.asteroid_count.base = 0,
+        "endless.asteroid_count: `base` must be between 1 and 40, got 0",
+    );
+    assert_problem(
+        |set| set.endless.asteroid_count.base = 100,
+        "endless.asteroid_count: `base` must be between 1 and 40, got 100",
+    );
+}
+
+#[test]
+fn reports_negative_endless_step() {
+    assert_problem(
+        |set| set.endless.enemy_spawn_interval.step = -0.5,
+        "endless.enemy_spawn_interval: `step` must not be negative, got -0.5",
+    );
+}
+
+#[test]
+fn reports_endless_min_of_zero() {
+    assert_problem(
+        |set| set.endless.enemy_spawn_interval.min = 0.0,
+        "endless.enemy_spawn_interval: `min` must be greater than 0, got 0",
+    );
+}
+
+#[test]
+fn reports_endless_start_below_min() {
+    assert_problem(
+        |set| set.endless.enemy_spawn_interval.start = 0.2,
+        "endless.enemy_spawn_interval: `start` must not be below `min`",
+    );
+}
+
+#[test]
+fn generated_levels_cap_the_asteroid_count() {
+    let set = shipped();
+    assert_eq!(set.get(1000).asteroid_count, MAX_ASTEROID_COUNT);
+}
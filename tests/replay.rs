@@ -0,0 +1,194 @@
+// Records a seeded run with scripted input and checks the replay reproduces it
+use macroquad::prelude::*;
+use rust_in_space::components::PlayerInput;
+use rust_in_space::data::{GameData, DATA_DIR, TUNING_FILE};
+use rust_in_space::game::{update_game, Game, TICK_DT};
+use rust_in_space::replay::{Replay, ReplayPlayer, ReplayRecorder, ReplayStep, REPLAY_VERSION};
+use serde_json::json;
+use std::fs;
+use std::path::Path;
+
+const ARENA: Vec2 = vec2(800.0, 600.0);
+const SEED: u64 = 7;
+const TICKS: u32 = 1200;
+const TUNING_CHANGE_TICK: u32 = 400;
+
+fn new_game() -> Game {
+    let data = GameData::load(Path::new(DATA_DIR)).expect("shipped data loads");
+    Game::new(ARENA, SEED, data)
+}
+
+// Turns back and forth, thrusts in bursts and fires most of the time
+fn scripted_input(tick: u32) -> PlayerInput {
+    PlayerInput {
+        turn: (tick as f32 / 40.0).sin(),
+        thrust: if tick % 90 < 45 { 0.6 } else { 0.0 },
+        fire: !tick.is_multiple_of(7),
+        ..Default::default()
+    }
+    .quantized()
+}
+
+struct Outcome {
+    pos: Vec2,
+    rotation: f32,
+    health: f32,
+    score: u32,
+    kills: u32,
+}
+
+fn outcome(game: &Game) -> Outcome {
+    Outcome {
+        pos: game.ship.pos,
+        rotation: game.ship.rotation,
+        health: game.ship.health,
+        score: game.score,
+        kills: game.mission_kills,
+    }
+}
+
+// Mirrors the live fixed-step loop of the Playing state
+fn record_run() -> (Replay, Outcome) {
+    let mut game = new_game();
+    game.reset(SEED);
+    let mut recorder = ReplayRecorder::new(game.seed, game.difficulty);
+    game.start_mission();
+    recorder.begin_mission(&game);
+
+    for tick in 0..TICKS {
+        if game.is_mission_complete() {
+            break;
+        }
+        if tick == TUNING_CHANGE_TICK {
+            // As if tuning.json was edited while playing
+            game.data.tuning.acceleration *= 2.0;
+            game.data.tuning.rotation_speed *= 0.5;
+        }
+        let input = scripted_input(tick);
+        recorder.record(&input, &game);
+        if update_game(&mut game, &input, TICK_DT) {
+            break;
+        }
+    }
+    (recorder.replay, outcome(&game))
+}
+
+fn play_back(replay: Replay) -> Outcome {
+    let mut game = new_game();
+    let mut player = ReplayPlayer::start(replay, &mut game);
+    // Runs until the recorded input ends or the ship is destroyed
+    while let ReplayStep::Running | ReplayStep::Paused = player.step(&mut game) {}
+    outcome(&game)
+}
+
+#[test]
+fn replay_reproduces_the_recorded_run() {
+    let (replay, live) = record_run();
+    // The script has to shoot something for the comparison to mean much
+    assert!(live.score > 0);
+    // Through the file format, like a replay loaded from disk
+    let json = serde_json::to_string(&replay).unwrap();
+    let loaded: Replay = serde_json::from_str(&json).unwrap();
+    let played = play_back(loaded);
+
+    assert_eq!(played.pos, live.pos);
+    assert_eq!(played.rotation, live.rotation);
+    assert_eq!(played.health, live.health);
+    assert_eq!(played.score, live.score);
+    assert_eq!(played.kills, live.kills);
+}
+
+#[test]
+fn repeated_input_is_stored_as_runs() {
+    let (replay, _) = record_run();
+    let track = &replay.missions[0];
+    let ticks: u32 = track.inputs.iter().map(|&(_, count)| count).sum();
+    assert!(ticks > 0);
+    assert!(track.inputs.len() < ticks as usize);
+    // Neighbouring runs always differ, or they would have been merged
+    for pair in track.inputs.windows(2) {
+        assert_ne!(pair[0].0, pair[1].0);
+    }
+}
+
+#[test]
+fn tuning_reloads_are_stored_on_their_tick() {
+    let (replay, _) = record_run();
+    let track = &replay.missions[0];
+    assert_eq!(track.tuning.len(), 2);
+    assert_eq!(track.tuning[0].0, 0);
+    assert_eq!(track.tuning[1].0, TUNING_CHANGE_TICK);
+    assert_eq!(
+        track.tuning[1].1.acceleration,
+        track.tuning[0].1.acceleration * 2.0
+    );
+}
+
+#[test]
+fn quantized_input_survives_the_round_trip() {
+    let input = PlayerInput {
+        turn: -0.37,
+        thrust: 0.81,
+        reverse: 0.25,
+        brake: true,
+        fire: true,
+        pause: true,
+        aim: Some(123.4),
+        thrust_heading: Some(-90.0),
+    };
+    let quantized = input.quantized();
+    assert!(quantized.quantized() == quantized);
+    assert!((quantized.turn - input.turn).abs() <= 1.0 / 127.0);
+    assert!((quantized.thrust - input.thrust).abs() <= 1.0 / 255.0);
+    assert!((quantized.reverse - input.reverse).abs() <= 1.0 / 255.0);
+    assert!(quantized.brake && quantized.fire && quantized.pause);
+    assert!((quantized.aim.unwrap() - 123.4).abs() < 0.01);
+    // Headings are stored as a fraction of a full turn
+    assert!((quantized.thrust_heading.unwrap() - 270.0).abs() < 0.01);
+}
+
+#[test]
+fn keyboard_only_replays_are_converted() {
+    let old = json!({
+        "version": 1,
+        "seed": 1,
+        "difficulty": "Nebula",
+        // Rotate left and fire for 30 ticks, then thrust for 10
+        "missions": [{ "level": 1, "arena": [800.0, 600.0], "inputs": [[9, 30], [4, 10]] }],
+    });
+    let replay = Replay::parse(&old.to_string()).unwrap();
+    assert_eq!(replay.version, REPLAY_VERSION);
+    let track = &replay.missions[0];
+    assert!(track.loadout.is_none());
+    // Fire, the turn as -127 from bit 8 and full thrust from bit 16
+    assert_eq!(track.inputs, vec![(1 | 0x81 << 8, 30), (0xff << 16, 10)]);
+}
+
+#[test]
+fn version_3_tuning_gets_the_handling_it_lacked() {
+    let path = Path::new(DATA_DIR).join(TUNING_FILE);
+    let mut tuning: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+    let fields = tuning.as_object_mut().unwrap();
+    for field in ["reverse_acceleration", "brake_deceleration", "max_speed"] {
+        fields.remove(field);
+    }
+    let old = json!({
+        "version": 3,
+        "seed": 1,
+        "difficulty": "Nebula",
+        "missions": [{ "level": 1, "arena": [800.0, 600.0], "inputs": [], "tuning": [[0, tuning]] }],
+    });
+    let replay = Replay::parse(&old.to_string()).unwrap();
+    let tuning = &replay.missions[0].tuning[0].1;
+    assert_eq!(tuning.reverse_acceleration, 0.0);
+    assert_eq!(tuning.brake_deceleration, 0.0);
+    assert_eq!(tuning.max_speed, 420.0);
+}
+
+#[test]
+fn newer_replays_are_rejected() {
+    let new =
+        json!({ "version": REPLAY_VERSION + 1, "seed": 1, "difficulty": "Nebula", "missions": [] });
+    assert!(Replay::parse(&new.to_string()).is_err());
+}
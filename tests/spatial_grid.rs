@@ -0,0 +1,89 @@
+// Checks the collision broadphase finds what the exact tests need, including
+// circles that touch across the wrap-around seam, and that the exact tests
+// only count what touches on screen
+use macroquad::prelude::*;
+use rust_in_space::components::{Asteroid, PlayerInput};
+use rust_in_space::data::{GameData, DATA_DIR};
+use rust_in_space::game::{update_game, Game, TICK_DT};
+use rust_in_space::spatial::SpatialGrid;
+use rust_in_space::systems::WRAP_MARGIN;
+use std::path::Path;
+
+const ARENA: Vec2 = vec2(800.0, 600.0);
+const CELL: f32 = 80.0;
+
+fn query(grid: &SpatialGrid, pos: Vec2, radius: f32) -> Vec<usize> {
+    let mut out = Vec::new();
+    grid.query(pos, radius, &mut out);
+    out
+}
+
+#[test]
+fn finds_nearby_entities_only() {
+    let mut grid = SpatialGrid::new(ARENA, CELL);
+    grid.insert(0, vec2(100.0, 100.0), 10.0);
+    grid.insert(1, vec2(120.0, 110.0), 10.0);
+    grid.insert(2, vec2(500.0, 400.0), 10.0);
+
+    let found = query(&grid, vec2(110.0, 105.0), 5.0);
+    assert!(found.contains(&0) && found.contains(&1));
+    assert!(!found.contains(&2));
+}
+
+#[test]
+fn results_are_sorted_without_duplicates() {
+    let mut grid = SpatialGrid::new(ARENA, CELL);
+    // Big circles land in several cells each
+    grid.insert(3, vec2(300.0, 300.0), 100.0);
+    grid.insert(1, vec2(320.0, 300.0), 100.0);
+    grid.insert(2, vec2(300.0, 320.0), 100.0);
+
+    assert_eq!(query(&grid, vec2(310.0, 310.0), 120.0), vec![1, 2, 3]);
+}
+
+#[test]
+fn circles_wider_than_the_arena_find_everything() {
+    let mut grid = SpatialGrid::new(ARENA, CELL);
+    grid.insert(0, vec2(0.0, 0.0), 1.0);
+    grid.insert(1, ARENA, 1.0);
+    grid.insert(2, ARENA / 2.0, 1.0);
+
+    assert_eq!(query(&grid, ARENA / 2.0, 2000.0), vec![0, 1, 2]);
+}
+
+#[test]
+fn finds_entities_across_the_seam() {
+    let mut grid = SpatialGrid::new(ARENA, CELL);
+    // Just past the right edge and just past the left edge: 10 units apart
+    // once the band wraps
+    let right = vec2(ARENA.x + WRAP_MARGIN - 5.0, 300.0);
+    let left = vec2(-WRAP_MARGIN + 5.0, 300.0);
+    grid.insert(0, right, 10.0);
+    assert_eq!(query(&grid, left, 10.0), vec![0]);
+
+    let bottom = vec2(400.0, ARENA.y + WRAP_MARGIN - 2.0);
+    let top = vec2(400.0, 2.0);
+    grid.insert(1, bottom, 30.0);
+    assert!(query(&grid, top, 30.0).contains(&1));
+}
+
+#[test]
+fn nothing_collides_across_the_seam() {
+    let data = GameData::load(Path::new(DATA_DIR)).expect("shipped data loads");
+    let mut game = Game::new(ARENA, 1, data);
+    game.start_mission();
+    game.enemy_ships.clear();
+    // Half over the right edge, touching the ship at the left edge once the
+    // band wraps, but the whole screen apart where the player sees them
+    let mut asteroid = Asteroid::new_large(ARENA, &game.rng);
+    asteroid.pos = vec2(ARENA.x + 5.0, 300.0);
+    asteroid.prev_pos = asteroid.pos;
+    asteroid.vel = Vec2::ZERO;
+    game.asteroids = vec![asteroid];
+    game.ship.pos = vec2(5.0, 300.0);
+    let health = game.ship.health;
+
+    update_game(&mut game, &PlayerInput::default(), TICK_DT);
+    assert_eq!(game.ship.health, health);
+    assert_eq!(game.asteroids.len(), 1);
+}
@@ -0,0 +1,155 @@
+// Lists the keys each translation is missing compared to English, so translators
+// can see what is left to do by running `cargo test`
+use rust_in_space::data::load_validated;
+use rust_in_space::localization::{load_language, Language, Localization, Text, LANG_DIR};
+use rust_in_space::missions::{mission_text, MissionSet};
+use std::path::Path;
+
+#[test]
+fn every_language_has_every_key() {
+    let dir = Path::new(LANG_DIR);
+    let english = load_language(dir, Language::English).unwrap_or_else(|e| panic!("{e}"));
+
+    let mut report = Vec::new();
+    for lang in Language::ALL {
+        let dict = load_language(dir, lang).unwrap_or_else(|e| panic!("{e}"));
+        let mut missing: Vec<&str> = english
+            .keys()
+            .filter(|key| !dict.contains_key(*key))
+            .map(String::as_str)
+            .collect();
+        let mut unknown: Vec<&str> = dict
+            .keys()
+            .filter(|key| !english.contains_key(*key))
+            .map(String::as_str)
+            .collect();
+        missing.sort();
+        unknown.sort();
+
+        if !missing.is_empty() {
+            report.push(format!(
+                "{}.json is missing: {}",
+                lang.code(),
+                missing.join(", ")
+            ));
+        }
+        // Plural texts need every form the language uses
+        let mut keys: Vec<&String> = dict.keys().collect();
+        keys.sort();
+        for key in keys {
+            if let Text::Plural(plural) = &dict[key] {
+                let forms: Vec<&str> = lang
+                    .plural_forms()
+                    .iter()
+                    .copied()
+                    .filter(|form| plural.get(form).is_none())
+                    .collect();
+                if !forms.is_empty() {
+                    report.push(format!(
+                        "{}.json \"{key}\" is missing plural forms: {}",
+                        lang.code(),
+                        forms.join(", ")
+                    ));
+                }
+            }
+        }
+        if !unknown.is_empty() {
+            report.push(format!(
+                "{}.json has keys English does not: {}",
+                lang.code(),
+                unknown.join(", ")
+            ));
+        }
+    }
+
+    assert!(report.is_empty(), "\n{}", report.join("\n"));
+}
+
+#[test]
+fn plural_forms_follow_the_language_rules() {
+    let ru = Language::Russian;
+    let forms: Vec<&str> = [1, 2, 5, 11, 12, 21, 22, 25, 101, 111]
+        .iter()
+        .map(|&n| ru.plural_form(n))
+        .collect();
+    assert_eq!(
+        forms,
+        ["one", "few", "many", "many", "many", "one", "few", "many", "one", "many"]
+    );
+    assert_eq!(Language::English.plural_form(1), "one");
+    assert_eq!(Language::English.plural_form(0), "other");
+    assert_eq!(Language::German.plural_form(2), "other");
+}
+
+#[test]
+fn format_fills_placeholders_and_picks_plurals() {
+    let mut lang = Localization::load(Path::new(LANG_DIR));
+    assert_eq!(
+        lang.format("obj_destroy", &[("count", 1.into())]),
+        "- Destroy 1 Enemy"
+    );
+    assert_eq!(
+        lang.format("obj_destroy", &[("count", 3.into())]),
+        "- Destroy 3 Enemies"
+    );
+    assert_eq!(
+        lang.format("level_cleared", &[("level", 4.into())]),
+        "Level 4 Cleared"
+    );
+
+    lang.current_lang = Language::Russian;
+    assert_eq!(
+        lang.format("obj_scrap", &[("count", 22.into())]),
+        "- Собрать 22 кучи лома"
+    );
+    assert_eq!(
+        lang.format("obj_scrap", &[("count", 5.into())]),
+        "- Собрать 5 куч лома"
+    );
+}
+
+#[test]
+fn mission_texts_pick_a_plural_form_per_count() {
+    let mut lang = Localization::load(Path::new(LANG_DIR));
+    let missions = load_validated::<MissionSet>(Path::new("assets/data/missions.json"))
+        .unwrap_or_else(|e| panic!("{e}"));
+    let mut mission = missions.get(3);
+    mission.target_scrap = 1;
+    mission.target_rare_metal = 3;
+    assert_eq!(
+        mission_text(&mission, "mission_scrap_yard_desc", &lang),
+        "Collect 1 rust pile and 3 gold for upgrades."
+    );
+
+    lang.current_lang = Language::Russian;
+    assert_eq!(
+        mission_text(&mission, "mission_scrap_yard_desc", &lang),
+        "Собери 1 кучу лома и 3 слитка золота для улучшений."
+    );
+    mission.target_scrap = 12;
+    mission.target_rare_metal = 21;
+    assert_eq!(
+        mission_text(&mission, "mission_scrap_yard_desc", &lang),
+        "Собери 12 куч лома и 21 слиток золота для улучшений."
+    );
+}
+
+#[test]
+fn mission_texts_are_translated() {
+    let english =
+        load_language(Path::new(LANG_DIR), Language::English).unwrap_or_else(|e| panic!("{e}"));
+    let missions = load_validated::<MissionSet>(Path::new("assets/data/missions.json"))
+        .unwrap_or_else(|e| panic!("{e}"));
+
+    let keys = missions
+        .missions
+        .iter()
+        .flat_map(|m| [&m.title_key, &m.description_key])
+        .chain([
+            &missions.endless.title_key,
+            &missions.endless.description_key,
+        ]);
+    for key in keys {
+        assert!(english.contains_key(key), "en.json has no \"{key}\"");
+    }
+}